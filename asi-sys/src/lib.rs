@@ -56,4 +56,33 @@ impl Default for ASI_SUPPORTED_MODE {
     fn default() -> Self {
         Self { SupportedCameraMode: [Default::default(); 16] }
     }
-}
\ No newline at end of file
+}
+
+impl Default for ASI_DATE_TIME {
+    fn default() -> Self {
+        Self {
+            Year: Default::default(),
+            Month: Default::default(),
+            Day: Default::default(),
+            Hour: Default::default(),
+            Minute: Default::default(),
+            Second: Default::default(),
+            Msecond: Default::default(),
+            Usecond: Default::default(),
+            Unused: [Default::default(); 64],
+        }
+    }
+}
+
+impl Default for ASI_GPS_DATA {
+    fn default() -> Self {
+        Self {
+            Datetime: Default::default(),
+            Latitude: Default::default(),
+            Longitude: Default::default(),
+            Altitude: Default::default(),
+            SatelliteNum: Default::default(),
+            Unused: [Default::default(); 64],
+        }
+    }
+}
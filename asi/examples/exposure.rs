@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use asi::*;
 
 fn main() {
@@ -8,39 +10,14 @@ fn main() {
     }
 
     let camera_info = camera_property(0).unwrap();
-
-    let width = camera_info.max_width;
-    let height = camera_info.max_height;
-    let color = camera_info.is_color_cam;
-    let bit_depth = camera_info.bit_depth > 1;
-
     let camera = Camera::open(camera_info.camera_id).unwrap();
 
-    if color {
-        camera.set_roi_format(width, height, 1, ImgType::Rgb24).unwrap();
-    } else {
-        camera.set_roi_format(width, height, 1, ImgType::Raw8).unwrap();
-    }
-
-    camera.start_exposure(false).unwrap();
-    while camera.exposure_status().unwrap() == ExposureStatus::Working {}
-
-    let mut buffer_size = width*height;
-    if color {
-        buffer_size *= 3;
-    } else if bit_depth {
-        buffer_size *= 2;
-    }
+    let img_type = if camera_info.is_color_cam { ImgType::Rgb24 } else { ImgType::Raw8 };
+    let roi = Roi::full().format(img_type).build(&camera_info).unwrap();
+    camera.set_roi(&roi).unwrap();
 
-    let mut data = vec![0; buffer_size as usize];
-    camera.get_data_after_exposure(&mut data).unwrap();
-    
-    let color = match (color, bit_depth) {
-        (true, _) => image::ColorType::Rgb8,
-        (false, true) => image::ColorType::L16,
-        (false, false) => image::ColorType::L8,
-    };
+    let frame = camera.expose(Duration::from_millis(100), false, Duration::from_secs(10)).unwrap();
 
-    image::save_buffer("exposure.jpg", &data, width as u32, height as u32, color).unwrap();
+    frame.save("exposure.png").unwrap();
     camera.close().unwrap();
 }
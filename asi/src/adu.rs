@@ -0,0 +1,57 @@
+use crate::{Frame, ImgType};
+
+impl Frame {
+    /// Bits Raw16 data is shifted left by, 0 for 8 bit frames.
+    pub fn adu_shift(&self) -> u32 {
+        match self.img_type() {
+            ImgType::Raw16 => 16 - self.native_bit_depth(),
+            _ => 0,
+        }
+    }
+
+    /// Highest value the ADC can deliver, in native ADU.
+    /// 8 bit frames are treated as having 8 bits.
+    pub fn max_adu(&self) -> u16 {
+        match self.img_type() {
+            ImgType::Raw16 => (u32::MAX >> (32 - self.native_bit_depth())) as u16,
+            _ => u8::MAX.into(),
+        }
+    }
+
+    /// Value of a saturated pixel in the data of the frame, eg. ```0xfff0``` for a 12 bit camera.
+    pub fn saturation_level(&self) -> u16 {
+        self.max_adu() << self.adu_shift()
+    }
+
+    /// Number of values which reached the saturation level.
+    pub fn saturated_pixels(&self) -> usize {
+        let level = self.saturation_level();
+        match self.as_u16() {
+            Some(pixels) => pixels.iter().filter(|&&value| value >= level).count(),
+            None => self.as_u8().iter().filter(|&&value| u16::from(value) >= level).count(),
+        }
+    }
+
+    /// Raw16 pixels in native ADU, shifted right by ```adu_shift```. ```None``` for 8 bit frames.
+    pub fn native_adu(&self) -> Option<impl Iterator<Item = u16> + '_> {
+        let shift = self.adu_shift();
+        Some(self.as_u16()?.iter().map(move |&value| value >> shift))
+    }
+
+    /// Raw16 pixels scaled so the saturation level becomes ```u16::MAX```. ```None``` for 8 bit frames.
+    pub fn full_scale(&self) -> Option<impl Iterator<Item = u16> + '_> {
+        let max_adu = u32::from(self.max_adu());
+        Some(self.native_adu()?.map(move |adu| ((u32::from(adu) * u32::from(u16::MAX) + max_adu / 2) / max_adu) as u16))
+    }
+
+    /// Raw16 pixels in electrons, ```elec_per_adu``` is the gain in electrons per native ADU,
+    /// eg. ```CameraInfo::elec_per_adu```. ```None``` for 8 bit frames.
+    pub fn electrons(&self, elec_per_adu: f32) -> Option<impl Iterator<Item = f32> + '_> {
+        Some(self.native_adu()?.map(move |adu| f32::from(adu) * elec_per_adu))
+    }
+
+    /// The bit depth of the format, limited to 1 to 16 bits.
+    fn native_bit_depth(&self) -> u32 {
+        self.bit_depth().clamp(1, 16)
+    }
+}
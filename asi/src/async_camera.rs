@@ -0,0 +1,141 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+use tokio::task::JoinError;
+
+use crate::{Camera, CancelToken, Error, ErrorCode, Frame, PooledFrame, Result};
+
+/// Number of video frames which can wait in a ```VideoFrames``` stream.
+const VIDEO_CHANNEL_SIZE: usize = 4;
+
+/// Camera which runs the blocking SDK calls on tokio's blocking thread pool.
+///
+/// Only one operation uses the camera at a time, others wait for it.
+#[derive(Debug, Clone)]
+pub struct AsyncCamera {
+    camera: Arc<Mutex<Camera>>,
+}
+
+impl From<Camera> for AsyncCamera {
+    fn from(camera: Camera) -> Self {
+        Self { camera: Arc::new(Mutex::new(camera)) }
+    }
+}
+
+impl AsyncCamera {
+    /// Open and initialize the camera.
+    pub async fn open(camera_id: u8) -> Result<Self> {
+        let camera = tokio::task::spawn_blocking(move || Camera::open(camera_id)).await.map_err(join_error)??;
+        Ok(camera.into())
+    }
+
+    /// Run any blocking function with the camera.
+    ///
+    /// A panic of the function is resumed, ```Error::Cancelled``` is returned if the runtime shuts down.
+    pub async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Camera) -> T + Send + 'static,
+    {
+        let camera = self.camera.clone();
+        tokio::task::spawn_blocking(move || f(&lock(&camera))).await.map_err(join_error)
+    }
+
+    /// Take a single exposure, see ```Camera::expose```.
+    ///
+    /// Dropping the future stops the exposure.
+    pub async fn expose(&self, duration: Duration, is_dark: bool, timeout: Duration) -> Result<Frame> {
+        let cancel = CancelOnDrop(CancelToken::new());
+        let token = cancel.0.clone();
+        self.run(move |camera| camera.expose_cancellable(duration, is_dark, timeout, &token)).await?
+    }
+
+    /// Start video capture and stream the frames, waiting ```wait_ms``` for each one.
+    ///
+    /// Timeouts of single frames are skipped. Any other error ends the stream.
+    /// Dropping the stream stops the video capture.
+    /// The frames come from a ```VideoStream```, so their buffers are reused once they're dropped.
+    ///
+    /// The stream keeps the camera locked until it's dropped. Meanwhile ```run``` and ```expose```
+    /// wait for it and each of them occupies a thread of the blocking pool.
+    ///
+    /// # Panics
+    ///
+    /// If it's called outside of a tokio runtime.
+    pub fn video_stream(&self, wait_ms: u32) -> VideoFrames {
+        let (sender, receiver) = mpsc::channel(VIDEO_CHANNEL_SIZE);
+        let cancel = CancelToken::new();
+        let token = cancel.clone();
+        let camera = self.camera.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let camera = lock(&camera);
+            let mut stream = match camera.video_stream(wait_ms) {
+                Ok(stream) => stream,
+                Err(error) => {
+                    let _ = sender.blocking_send(Err(error));
+                    return;
+                }
+            };
+
+            // Dropping the stream stops the capture.
+            while !token.is_cancelled() {
+                let result = stream.next_frame();
+                let failed = match &result {
+                    Err(error) if error.code() == Some(ErrorCode::Timeout) => continue,
+                    result => result.is_err(),
+                };
+                if sender.blocking_send(result).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        VideoFrames { receiver, _cancel: CancelOnDrop(cancel) }
+    }
+
+    /// Get the camera back, ```None``` if it's still used by a clone or a running stream.
+    pub fn into_inner(self) -> Option<Camera> {
+        Arc::into_inner(self.camera).map(|camera| camera.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Stream of video frames from ```AsyncCamera::video_stream```.
+#[derive(Debug)]
+pub struct VideoFrames {
+    receiver: mpsc::Receiver<Result<PooledFrame>>,
+    _cancel: CancelOnDrop,
+}
+
+impl Stream for VideoFrames {
+    type Item = Result<PooledFrame>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Cancels the token when dropped.
+#[derive(Debug)]
+struct CancelOnDrop(CancelToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+fn lock(camera: &Mutex<Camera>) -> MutexGuard<'_, Camera> {
+    camera.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn join_error(error: JoinError) -> Error {
+    match error.try_into_panic() {
+        Ok(panic) => std::panic::resume_unwind(panic),
+        Err(_) => Error::Cancelled,
+    }
+}
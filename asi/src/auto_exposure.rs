@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use crate::{Camera, ControlType, Error, Frame, Result, StatsOptions};
+
+/// Largest factor the exposure is changed by in one step, the sensor isn't linear close to black or saturation.
+const MAX_STEP: f64 = 10.0;
+/// A median above this fraction of the full scale is treated as saturated.
+const SATURATED: f64 = 0.98;
+/// Extra time for the download of a test exposure.
+const READOUT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Host side auto exposure for single exposures, eg. for flats.
+///
+/// Test exposures are taken until the median of the brightest channel reaches the target.
+/// The SDK's auto controls only work for video capture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoExposure {
+    /// Target median as a fraction of the full scale of the bit depth, eg. 0.5.
+    pub target: f64,
+    /// Accepted distance of the median from the target, as a fraction of the full scale.
+    pub tolerance: f64,
+    /// Exposure of the first test exposure.
+    pub initial_exposure: Duration,
+    /// Limits of the exposure, they're also limited to the range of the camera.
+    pub min_exposure: Duration,
+    pub max_exposure: Duration,
+    /// Also change the gain if the exposure limits aren't enough, within the range of the camera.
+    pub adjust_gain: bool,
+    pub max_iterations: u32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self {
+            target: 0.5,
+            tolerance: 0.05,
+            initial_exposure: Duration::from_millis(100),
+            min_exposure: Duration::ZERO,
+            max_exposure: Duration::from_secs(60),
+            adjust_gain: false,
+            max_iterations: 10,
+        }
+    }
+}
+
+/// One test exposure of ```AutoExposure::run```.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoExposureStep {
+    pub exposure: Duration,
+    pub gain: i32,
+    /// Median of the brightest channel as a fraction of the full scale.
+    pub median: f64,
+}
+
+/// Result of ```AutoExposure::run```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoExposureResult {
+    /// The settings which reached the target or came closest to it. They're also set on the camera.
+    pub exposure: Duration,
+    pub gain: i32,
+    pub converged: bool,
+    /// All test exposures in order.
+    pub steps: Vec<AutoExposureStep>,
+    /// The test exposure with the returned settings.
+    pub frame: Frame,
+}
+
+impl AutoExposureResult {
+    pub fn iterations(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+impl AutoExposure {
+    pub fn new(target: f64) -> Self {
+        Self { target, ..Self::default() }
+    }
+
+    /// Take test exposures until the target is reached, the limits are hit or ```max_iterations``` are used up.
+    /// Without reaching the target the settings of the step closest to it are used.
+    pub fn run(&self, camera: &Camera) -> Result<AutoExposureResult> {
+        self.validate()?;
+        let exposure_caps = camera.caps(ControlType::Exposure).ok_or(Error::UnsupportedControl(ControlType::Exposure))?;
+        let min_exposure = self.min_exposure.max(Duration::from_micros(exposure_caps.min_value.max(1) as u64));
+        let max_exposure = self.max_exposure.min(Duration::from_micros(exposure_caps.max_value.max(0) as u64)).max(min_exposure);
+        let gain_limits = match camera.caps(ControlType::Gain) {
+            Some(caps) if self.adjust_gain => Some((caps.min_value, caps.max_value)),
+            _ => None,
+        };
+
+        let mut exposure = self.initial_exposure.clamp(min_exposure, max_exposure);
+        let mut gain = 0;
+        if camera.supports(ControlType::Gain) {
+            gain = camera.gain()?.value;
+            // Auto gain would change the brightness between the test exposures.
+            camera.set_gain(gain)?;
+        }
+        let mut steps = Vec::new();
+        // The closest step so far and its frame.
+        let mut best: Option<(AutoExposureStep, Frame)> = None;
+
+        loop {
+            let frame = camera.expose(exposure, false, exposure * 2 + READOUT_TIMEOUT)?;
+            let step = AutoExposureStep { exposure, gain, median: brightest_median(&frame) };
+            steps.push(step);
+
+            let converged = self.distance(&step) <= self.tolerance;
+            if converged {
+                return Ok(AutoExposureResult { exposure, gain, converged, steps, frame });
+            }
+            if best.as_ref().is_none_or(|(best, _)| self.distance(&step) < self.distance(best)) {
+                best = Some((step, frame));
+            }
+
+            let (next_exposure, next_gain) = self.next_settings(&step, (min_exposure, max_exposure), gain_limits);
+            // Out of iterations or stuck at the limits.
+            if steps.len() >= self.max_iterations as usize || (next_exposure == exposure && next_gain == gain) {
+                let (best, frame) = best.unwrap();
+                if best.exposure != exposure {
+                    camera.set_exposure(best.exposure)?;
+                }
+                if best.gain != gain {
+                    camera.set_gain(best.gain)?;
+                }
+                return Ok(AutoExposureResult { exposure: best.exposure, gain: best.gain, converged: false, steps, frame });
+            }
+            if next_gain != gain {
+                camera.set_gain(next_gain)?;
+            }
+            exposure = next_exposure;
+            gain = next_gain;
+        }
+    }
+
+    /// Check that the target is within the full scale and the tolerance isn't negative.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.target.is_finite() && self.target > 0.0 && self.target <= 1.0) {
+            return Err(Error::InvalidAutoExposure(format!("target {} isn't in (0, 1]", self.target)));
+        }
+        if !(self.tolerance.is_finite() && self.tolerance >= 0.0) {
+            return Err(Error::InvalidAutoExposure(format!("tolerance {} is negative or not finite", self.tolerance)));
+        }
+        Ok(())
+    }
+
+    /// Exposure and gain for the test exposure after ```step```, within the exposure limits.
+    /// The gain is only changed with ```gain_limits``` and when the exposure limits aren't enough.
+    pub fn next_settings(&self, step: &AutoExposureStep, exposure_limits: (Duration, Duration), gain_limits: Option<(i32, i32)>) -> (Duration, i32) {
+        let factor = self.correction(step.median);
+        let exposure = step.exposure.as_secs_f64();
+        // Only an invalid target makes the exposure unrepresentable, then it's kept.
+        let next_exposure = Duration::try_from_secs_f64(exposure * factor).unwrap_or(step.exposure).clamp(exposure_limits.0, exposure_limits.1);
+        // The exposure can't fix the rest of the correction, the gain is in 0.1 dB.
+        let remaining = factor * exposure / next_exposure.as_secs_f64();
+        let next_gain = match gain_limits {
+            Some((min, max)) if (remaining - 1.0).abs() > 0.01 => (f64::from(step.gain) + 200.0 * remaining.log10()).round().clamp(f64::from(min), f64::from(max)) as i32,
+            _ => step.gain,
+        };
+        (next_exposure, next_gain)
+    }
+
+    /// Distance of the median of a step from the target.
+    fn distance(&self, step: &AutoExposureStep) -> f64 {
+        (step.median - self.target).abs()
+    }
+
+    /// Factor for the exposure, assuming the sensor is linear.
+    pub fn correction(&self, median: f64) -> f64 {
+        if median >= SATURATED {
+            return 1.0 / MAX_STEP;
+        }
+        match median > 0.0 {
+            true => (self.target / median).clamp(1.0 / MAX_STEP, MAX_STEP),
+            false => MAX_STEP,
+        }
+    }
+}
+
+/// Median of the brightest channel as a fraction of the full scale.
+fn brightest_median(frame: &Frame) -> f64 {
+    let stats = frame.stats_with(&StatsOptions::fast());
+    let full_scale = f64::from(frame.max_adu());
+    stats.channels.iter().map(|(_, stats)| stats.median / full_scale).fold(0.0, f64::max)
+}
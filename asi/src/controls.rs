@@ -0,0 +1,234 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Camera, ControlType, FlipStatus, Result};
+
+/// Value of a control together with its auto flag.
+///
+/// A plain value converts into a manual ```ControlValue```, so setters can be called like
+/// ```camera.set_gain(120)``` or ```camera.set_gain(ControlValue::auto(120))```.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ControlValue<T> {
+    pub value: T,
+    /// The camera adjusts the value itself, ```value``` is the starting point.
+    pub auto: bool,
+}
+
+impl<T> ControlValue<T> {
+    pub fn manual(value: T) -> Self {
+        Self { value, auto: false }
+    }
+
+    pub fn auto(value: T) -> Self {
+        Self { value, auto: true }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ControlValue<U> {
+        ControlValue { value: f(self.value), auto: self.auto }
+    }
+}
+
+impl<T> From<T> for ControlValue<T> {
+    fn from(value: T) -> Self {
+        Self::manual(value)
+    }
+}
+
+/// Temperature in degrees Celsius.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Celsius(pub f64);
+
+impl Celsius {
+    /// Converts a temperature in tenths of a degree, the unit of the sensor temperature.
+    pub fn from_tenths(tenths: i64) -> Self {
+        Self(tenths as f64 / 10.0)
+    }
+
+    /// Rounded to whole degrees, the unit of the target temperature.
+    pub fn whole_degrees(self) -> i64 {
+        self.0.round() as i64
+    }
+}
+
+impl fmt::Display for Celsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} °C", self.0)
+    }
+}
+
+/// White balance of a color camera.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WhiteBalance {
+    pub red: i32,
+    pub blue: i32,
+}
+
+impl ControlValue<WhiteBalance> {
+    /// Combines the ```WbR``` and ```WbB``` controls, it's auto if either channel is auto.
+    pub fn from_channels(red: ControlValue<i32>, blue: ControlValue<i32>) -> Self {
+        Self { value: WhiteBalance { red: red.value, blue: blue.value }, auto: red.auto || blue.auto }
+    }
+
+    /// The values of the ```WbR``` and ```WbB``` controls.
+    pub fn channels(self) -> (ControlValue<i32>, ControlValue<i32>) {
+        (self.map(|wb| wb.red), self.map(|wb| wb.blue))
+    }
+}
+
+impl ControlValue<Duration> {
+    /// Converts an exposure in microseconds, the unit of the SDK. Negative values become zero.
+    pub fn from_micros(micros: ControlValue<i64>) -> Self {
+        micros.map(|us| Duration::from_micros(us.max(0) as u64))
+    }
+
+    /// The exposure in whole microseconds, saturating at ```i64::MAX```.
+    pub fn micros(self) -> ControlValue<i64> {
+        self.map(|exposure| i64::try_from(exposure.as_micros()).unwrap_or(i64::MAX))
+    }
+}
+
+impl Camera {
+    fn typed_value(&self, control_type: ControlType) -> Result<ControlValue<i64>> {
+        let (value, auto) = self.control_value(control_type)?;
+        Ok(ControlValue { value: value as i64, auto })
+    }
+
+    fn set_typed_value(&self, control_type: ControlType, value: ControlValue<i64>) -> Result<()> {
+        self.set_raw_control_value(control_type, value.value, value.auto)
+    }
+
+    /// Get the exposure time.
+    pub fn exposure(&self) -> Result<ControlValue<Duration>> {
+        Ok(ControlValue::from_micros(self.typed_value(ControlType::Exposure)?))
+    }
+
+    /// Set the exposure time, the SDK works in microseconds.
+    /// Exposures which don't fit the control's range return ```Error::ControlOutOfRange```.
+    pub fn set_exposure(&self, exposure: impl Into<ControlValue<Duration>>) -> Result<()> {
+        self.set_typed_value(ControlType::Exposure, exposure.into().micros())
+    }
+
+    pub fn gain(&self) -> Result<ControlValue<i32>> {
+        Ok(self.typed_value(ControlType::Gain)?.map(|gain| gain as i32))
+    }
+
+    pub fn set_gain(&self, gain: impl Into<ControlValue<i32>>) -> Result<()> {
+        self.set_typed_value(ControlType::Gain, gain.into().map(i64::from))
+    }
+
+    pub fn offset(&self) -> Result<i32> {
+        Ok(self.typed_value(ControlType::Offset)?.value as i32)
+    }
+
+    pub fn set_offset(&self, offset: i32) -> Result<()> {
+        self.set_typed_value(ControlType::Offset, ControlValue::manual(offset.into()))
+    }
+
+    pub fn gamma(&self) -> Result<i32> {
+        Ok(self.typed_value(ControlType::Gamma)?.value as i32)
+    }
+
+    pub fn set_gamma(&self, gamma: i32) -> Result<()> {
+        self.set_typed_value(ControlType::Gamma, ControlValue::manual(gamma.into()))
+    }
+
+    /// Get the white balance, it's auto if either channel is auto.
+    pub fn white_balance(&self) -> Result<ControlValue<WhiteBalance>> {
+        let red = self.typed_value(ControlType::WbR)?.map(|red| red as i32);
+        let blue = self.typed_value(ControlType::WbB)?.map(|blue| blue as i32);
+        Ok(ControlValue::from_channels(red, blue))
+    }
+
+    pub fn set_white_balance(&self, white_balance: impl Into<ControlValue<WhiteBalance>>) -> Result<()> {
+        let (red, blue) = white_balance.into().channels();
+        self.set_typed_value(ControlType::WbR, red.map(i64::from))?;
+        self.set_typed_value(ControlType::WbB, blue.map(i64::from))
+    }
+
+    /// Get the sensor temperature, the SDK reports it multiplied by 10.
+    pub fn temperature(&self) -> Result<Celsius> {
+        Ok(Celsius::from_tenths(self.typed_value(ControlType::Temperature)?.value))
+    }
+
+    /// Get the target temperature of the cooler.
+    pub fn target_temperature(&self) -> Result<Celsius> {
+        Ok(Celsius(self.typed_value(ControlType::TargetTemp)?.value as f64))
+    }
+
+    /// Set the target temperature of the cooler, the SDK only takes whole degrees.
+    pub fn set_target_temperature(&self, temperature: Celsius) -> Result<()> {
+        self.set_typed_value(ControlType::TargetTemp, ControlValue::manual(temperature.whole_degrees()))
+    }
+
+    pub fn cooler_on(&self) -> Result<bool> {
+        Ok(self.typed_value(ControlType::CoolerOn)?.value != 0)
+    }
+
+    pub fn set_cooler_on(&self, on: bool) -> Result<()> {
+        self.set_typed_value(ControlType::CoolerOn, ControlValue::manual(on.into()))
+    }
+
+    /// Get the power of the cooler in percent.
+    pub fn cooler_power(&self) -> Result<u32> {
+        Ok(self.typed_value(ControlType::CoolerPowerPerc)?.value.max(0) as u32)
+    }
+
+    pub fn fan_on(&self) -> Result<bool> {
+        Ok(self.typed_value(ControlType::FanOn)?.value != 0)
+    }
+
+    pub fn set_fan_on(&self, on: bool) -> Result<()> {
+        self.set_typed_value(ControlType::FanOn, ControlValue::manual(on.into()))
+    }
+
+    pub fn flip(&self) -> Result<FlipStatus> {
+        Ok(FlipStatus::try_from(self.typed_value(ControlType::Flip)?.value as i32)?)
+    }
+
+    pub fn set_flip(&self, flip: FlipStatus) -> Result<()> {
+        self.set_typed_value(ControlType::Flip, ControlValue::manual(flip as i64))
+    }
+
+    pub fn bandwidth_overload(&self) -> Result<ControlValue<i32>> {
+        Ok(self.typed_value(ControlType::BandwidthOverflow)?.map(|bandwidth| bandwidth as i32))
+    }
+
+    pub fn set_bandwidth_overload(&self, bandwidth: impl Into<ControlValue<i32>>) -> Result<()> {
+        self.set_typed_value(ControlType::BandwidthOverflow, bandwidth.into().map(i64::from))
+    }
+
+    pub fn high_speed_mode(&self) -> Result<bool> {
+        Ok(self.typed_value(ControlType::HighSpeedMode)?.value != 0)
+    }
+
+    pub fn set_high_speed_mode(&self, on: bool) -> Result<()> {
+        self.set_typed_value(ControlType::HighSpeedMode, ControlValue::manual(on.into()))
+    }
+
+    /// Whether the camera has a GPS receiver.
+    pub fn gps_supported(&self) -> Result<bool> {
+        Ok(self.supports(ControlType::GpsSupport) && self.typed_value(ControlType::GpsSupport)?.value != 0)
+    }
+
+    /// Get the line of which the GPS data is the start line data of ```gps_line_data```.
+    pub fn gps_start_line(&self) -> Result<u32> {
+        Ok(self.typed_value(ControlType::GpsStartLine)?.value.max(0) as u32)
+    }
+
+    /// Set the start line, it has to be between 0 and ```max_height - 1```.
+    pub fn set_gps_start_line(&self, line: u32) -> Result<()> {
+        self.set_typed_value(ControlType::GpsStartLine, ControlValue::manual(line.into()))
+    }
+
+    /// Get the line of which the GPS data is the end line data of ```gps_line_data```.
+    pub fn gps_end_line(&self) -> Result<u32> {
+        Ok(self.typed_value(ControlType::GpsEndLine)?.value.max(0) as u32)
+    }
+
+    /// Set the end line, it has to be between 0 and ```max_height - 1```.
+    pub fn set_gps_end_line(&self, line: u32) -> Result<()> {
+        self.set_typed_value(ControlType::GpsEndLine, ControlValue::manual(line.into()))
+    }
+}
@@ -0,0 +1,310 @@
+//! Turning raw frames of color cameras into RGB images.
+//!
+//! The pattern of a frame has to match its first pixel. Frames from ```Camera``` already have the pattern
+//! shifted for the ROI start position and the flip, ```effective_pattern``` does this for other data.
+
+use crate::{BayerPattern, FlipStatus, Frame, ImgType};
+
+/// Demosaicing algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Algorithm {
+    /// Every 2x2 block becomes one pixel, the image has half the width and height.
+    Superpixel,
+    /// Missing colors are the average of the neighbours with that color.
+    Bilinear,
+    /// Bilinear with gradient correction by Malvar, He and Cutler, keeps edges sharper.
+    #[default]
+    MalvarHeCutler,
+}
+
+/// Pixel type of the raw data.
+pub trait Sample: Copy + Default {
+    const MAX: i32;
+
+    fn to_i32(self) -> i32;
+
+    /// Converts a value which is already clamped to ```0..=MAX```.
+    fn from_i32(value: i32) -> Self;
+}
+
+impl Sample for u8 {
+    const MAX: i32 = u8::MAX as i32;
+
+    fn to_i32(self) -> i32 {
+        self.into()
+    }
+
+    fn from_i32(value: i32) -> Self {
+        value as u8
+    }
+}
+
+impl Sample for u16 {
+    const MAX: i32 = u16::MAX as i32;
+
+    fn to_i32(self) -> i32 {
+        self.into()
+    }
+
+    fn from_i32(value: i32) -> Self {
+        value as u16
+    }
+}
+
+/// Image with interleaved red, green and blue values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbImage<T> {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<T>,
+}
+
+impl<T: Sample> RgbImage<T> {
+    /// The red, green and blue value of a pixel, ```None``` if it's outside of the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[T; 3]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = (y as usize * self.width as usize + x as usize) * 3;
+        Some([self.data[index], self.data[index + 1], self.data[index + 2]])
+    }
+}
+
+/// A debayered frame, 16 bit for Raw16.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorImage {
+    Rgb8(RgbImage<u8>),
+    Rgb16(RgbImage<u16>),
+}
+
+impl Frame {
+    /// Debayer a Raw8 or Raw16 frame, ```None``` if it has no bayer pattern or is smaller than 2x2 pixels.
+    pub fn debayer(&self, algorithm: Algorithm) -> Option<ColorImage> {
+        let pattern = self.bayer_pattern()?;
+        let (width, height) = (self.width(), self.height());
+        if width < 2 || height < 2 {
+            return None;
+        }
+        match self.img_type() {
+            ImgType::Raw8 => Some(ColorImage::Rgb8(debayer(self.as_u8(), width, height, pattern, algorithm))),
+            ImgType::Raw16 => Some(ColorImage::Rgb16(debayer(self.as_u16()?, width, height, pattern, algorithm))),
+            ImgType::Rgb24 | ImgType::Y8 => None,
+        }
+    }
+}
+
+/// The pattern at the first pixel of an image.
+///
+/// ```pattern``` is the one of the whole sensor from ```CameraInfo```, the ROI is given in binned pixels.
+/// An odd start position shifts the pattern, so does flipping an image with an even size.
+pub fn effective_pattern(pattern: BayerPattern, start_x: u32, start_y: u32, width: u32, height: u32, flip: FlipStatus) -> BayerPattern {
+    let flip_x = matches!(flip, FlipStatus::Horizontal | FlipStatus::Both);
+    let flip_y = matches!(flip, FlipStatus::Vertical | FlipStatus::Both);
+    // Position of the first pixel of the image on the sensor.
+    let x = if flip_x { start_x + width.saturating_sub(1) } else { start_x };
+    let y = if flip_y { start_y + height.saturating_sub(1) } else { start_y };
+
+    let (red_x, red_y) = red_position(pattern);
+    match ((red_x + x) % 2, (red_y + y) % 2) {
+        (0, 0) => BayerPattern::Rg,
+        (1, 0) => BayerPattern::Gr,
+        (0, 1) => BayerPattern::Gb,
+        _ => BayerPattern::Bg,
+    }
+}
+
+/// Debayer raw data of ```width``` x ```height``` pixels.
+///
+/// # Panics
+/// If the data is smaller than the image or the image is smaller than 2x2 pixels.
+pub fn debayer<T: Sample>(data: &[T], width: u32, height: u32, pattern: BayerPattern, algorithm: Algorithm) -> RgbImage<T> {
+    assert!(width >= 2 && height >= 2, "image of {}x{} pixels is too small to debayer", width, height);
+    assert!(data.len() >= width as usize * height as usize, "data is smaller than {}x{} pixels", width, height);
+
+    let mosaic = Mosaic { data, width: width as usize, height: height as usize, red: red_position(pattern) };
+    match algorithm {
+        Algorithm::Superpixel => mosaic.superpixel(),
+        Algorithm::Bilinear => mosaic.interpolate(|mosaic, x, y| mosaic.bilinear(x, y)),
+        Algorithm::MalvarHeCutler => mosaic.interpolate(|mosaic, x, y| mosaic.malvar_he_cutler(x, y)),
+    }
+}
+
+/// Column and row of the red pixel in the first 2x2 block.
+pub(crate) fn red_position(pattern: BayerPattern) -> (u32, u32) {
+    match pattern {
+        BayerPattern::Rg => (0, 0),
+        BayerPattern::Gr => (1, 0),
+        BayerPattern::Gb => (0, 1),
+        BayerPattern::Bg => (1, 1),
+    }
+}
+
+const RED: usize = 0;
+const GREEN: usize = 1;
+const BLUE: usize = 2;
+
+struct Mosaic<'a, T> {
+    data: &'a [T],
+    width: usize,
+    height: usize,
+    red: (u32, u32),
+}
+
+impl<T: Sample> Mosaic<'_, T> {
+    fn is_red_row(&self, y: usize) -> bool {
+        y % 2 == self.red.1 as usize
+    }
+
+    fn is_red_column(&self, x: usize) -> bool {
+        x % 2 == self.red.0 as usize
+    }
+
+    fn color(&self, x: usize, y: usize) -> usize {
+        match (self.is_red_column(x), self.is_red_row(y)) {
+            (true, true) => RED,
+            (false, false) => BLUE,
+            _ => GREEN,
+        }
+    }
+
+    /// Value at a position which may be outside of the image, it's mirrored at the edge pixels
+    /// so the color stays the same.
+    fn at(&self, x: isize, y: isize) -> i32 {
+        self.data[mirror(y, self.height) * self.width + mirror(x, self.width)].to_i32()
+    }
+
+    fn superpixel(&self) -> RgbImage<T> {
+        let (width, height) = (self.width / 2, self.height / 2);
+        let mut data = Vec::with_capacity(width * height * 3);
+        let (red_x, red_y) = (self.red.0 as isize, self.red.1 as isize);
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let (x, y) = (x * 2, y * 2);
+                let red = self.at(x + red_x, y + red_y);
+                let blue = self.at(x + 1 - red_x, y + 1 - red_y);
+                let green = (self.at(x + 1 - red_x, y + red_y) + self.at(x + red_x, y + 1 - red_y) + 1) / 2;
+                data.extend([red, green, blue].map(T::from_i32));
+            }
+        }
+        RgbImage { width: width as u32, height: height as u32, data }
+    }
+
+    fn interpolate(&self, pixel: impl Fn(&Self, usize, usize) -> [i32; 3]) -> RgbImage<T> {
+        let mut data = Vec::with_capacity(self.width * self.height * 3);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                data.extend(pixel(self, x, y).map(|value| T::from_i32(value.clamp(0, T::MAX))));
+            }
+        }
+        RgbImage { width: self.width as u32, height: self.height as u32, data }
+    }
+
+    fn bilinear(&self, x: usize, y: usize) -> [i32; 3] {
+        let mut sums = [0; 3];
+        let mut counts = [0; 3];
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (mirror(x as isize + dx, self.width), mirror(y as isize + dy, self.height));
+                let color = self.color(nx, ny);
+                sums[color] += self.data[ny * self.width + nx].to_i32();
+                counts[color] += 1;
+            }
+        }
+
+        let mut rgb = [0; 3];
+        for color in [RED, GREEN, BLUE] {
+            rgb[color] = (sums[color] + counts[color] / 2) / counts[color];
+        }
+        // The pixel's own color is exact, even if the 3x3 block has more pixels of it.
+        rgb[self.color(x, y)] = self.at(x as isize, y as isize);
+        rgb
+    }
+
+    /// Applies a 5x5 kernel given as offsets and weights in sixteenths.
+    fn kernel(&self, x: usize, y: usize, weights: &[(isize, isize, i32)]) -> i32 {
+        let sum: i32 = weights.iter().map(|&(dx, dy, weight)| weight * self.at(x as isize + dx, y as isize + dy)).sum();
+        (sum + 8).div_euclid(16)
+    }
+
+    fn malvar_he_cutler(&self, x: usize, y: usize) -> [i32; 3] {
+        let value = self.at(x as isize, y as isize);
+        match self.color(x, y) {
+            RED => [value, self.kernel(x, y, GREEN_AT_RED_BLUE), self.kernel(x, y, RED_BLUE_AT_BLUE_RED)],
+            BLUE => [self.kernel(x, y, RED_BLUE_AT_BLUE_RED), self.kernel(x, y, GREEN_AT_RED_BLUE), value],
+            _ if self.is_red_row(y) => [self.kernel(x, y, AT_GREEN_FROM_ROW), value, self.kernel(x, y, AT_GREEN_FROM_COLUMN)],
+            _ => [self.kernel(x, y, AT_GREEN_FROM_COLUMN), value, self.kernel(x, y, AT_GREEN_FROM_ROW)],
+        }
+    }
+}
+
+/// Mirrors a coordinate at the first and the last pixel, which keeps its parity.
+fn mirror(mut coordinate: isize, size: usize) -> usize {
+    let last = size as isize - 1;
+    loop {
+        if coordinate < 0 {
+            coordinate = -coordinate;
+        } else if coordinate > last {
+            coordinate = 2 * last - coordinate;
+        } else {
+            return coordinate as usize;
+        }
+    }
+}
+
+// The Malvar-He-Cutler kernels in sixteenths, as (dx, dy, weight).
+
+/// Green at a red or blue pixel.
+const GREEN_AT_RED_BLUE: &[(isize, isize, i32)] = &[
+    (0, -2, -2),
+    (0, -1, 4),
+    (-2, 0, -2),
+    (-1, 0, 4),
+    (0, 0, 8),
+    (1, 0, 4),
+    (2, 0, -2),
+    (0, 1, 4),
+    (0, 2, -2),
+];
+
+/// The color of the horizontal neighbours at a green pixel.
+const AT_GREEN_FROM_ROW: &[(isize, isize, i32)] = &[
+    (0, -2, 1),
+    (-1, -1, -2),
+    (1, -1, -2),
+    (-2, 0, -2),
+    (-1, 0, 8),
+    (0, 0, 10),
+    (1, 0, 8),
+    (2, 0, -2),
+    (-1, 1, -2),
+    (1, 1, -2),
+    (0, 2, 1),
+];
+
+/// The color of the vertical neighbours at a green pixel.
+const AT_GREEN_FROM_COLUMN: &[(isize, isize, i32)] = &[
+    (0, -2, -2),
+    (-1, -1, -2),
+    (0, -1, 8),
+    (1, -1, -2),
+    (-2, 0, 1),
+    (0, 0, 10),
+    (2, 0, 1),
+    (-1, 1, -2),
+    (0, 1, 8),
+    (1, 1, -2),
+    (0, 2, -2),
+];
+
+/// Red at a blue pixel or blue at a red pixel.
+const RED_BLUE_AT_BLUE_RED: &[(isize, isize, i32)] = &[
+    (0, -2, -3),
+    (-1, -1, 4),
+    (1, -1, 4),
+    (-2, 0, -3),
+    (0, 0, 12),
+    (2, 0, -3),
+    (-1, 1, 4),
+    (1, 1, 4),
+    (0, 2, -3),
+];
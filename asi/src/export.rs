@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use tiff::encoder::{colortype, TiffEncoder};
+use tiff::tags::Tag;
+
+use crate::fits::FitsWriter;
+use crate::{Frame, ImgType};
+
+impl Frame {
+    /// Save the frame as TIFF, PNG or FITS, chosen by the extension of the path.
+    ///
+    /// Raw16 keeps its 16 bits, Rgb24 is written in red, green, blue order.
+    /// The capture metadata is written into TIFF tags, PNG text chunks or FITS keywords.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("").to_ascii_lowercase();
+        match extension.as_str() {
+            "tif" | "tiff" => self.save_tiff(path),
+            "png" => self.save_png(path),
+            "fit" | "fits" | "fts" => FitsWriter::new().save(self, path),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unsupported image format {:?}", extension))),
+        }
+    }
+
+    fn save_tiff(&self, path: &Path) -> io::Result<()> {
+        let mut encoder = TiffEncoder::new(BufWriter::new(File::create(path)?)).map_err(io::Error::other)?;
+        let (width, height) = (self.width(), self.height());
+        match self.img_type() {
+            ImgType::Raw8 | ImgType::Y8 => {
+                let mut image = encoder.new_image::<colortype::Gray8>(width, height).map_err(io::Error::other)?;
+                self.write_tiff_tags(image.encoder()).map_err(io::Error::other)?;
+                image.write_data(self.as_u8()).map_err(io::Error::other)
+            }
+            ImgType::Raw16 => {
+                let mut image = encoder.new_image::<colortype::Gray16>(width, height).map_err(io::Error::other)?;
+                self.write_tiff_tags(image.encoder()).map_err(io::Error::other)?;
+                image.write_data(self.as_u16().unwrap()).map_err(io::Error::other)
+            }
+            ImgType::Rgb24 => {
+                let mut image = encoder.new_image::<colortype::RGB8>(width, height).map_err(io::Error::other)?;
+                self.write_tiff_tags(image.encoder()).map_err(io::Error::other)?;
+                image.write_data(&self.to_rgb()).map_err(io::Error::other)
+            }
+        }
+    }
+
+    fn write_tiff_tags<W: io::Write + io::Seek, K: tiff::encoder::TiffKind>(&self, encoder: &mut tiff::encoder::DirectoryEncoder<'_, W, K>) -> tiff::TiffResult<()> {
+        let Some(metadata) = self.metadata() else {
+            return Ok(());
+        };
+        encoder.write_tag(Tag::Make, "ZWO")?;
+        encoder.write_tag(Tag::Model, ascii(&metadata.camera_name).as_str())?;
+        // TIFF only has second precision in the format "YYYY:MM:DD HH:MM:SS".
+        let date = metadata.start.utc_string()[..19].replacen('-', ":", 2).replace('T', " ");
+        encoder.write_tag(Tag::DateTime, date.as_str())?;
+        let description: Vec<String> = self.text_metadata().iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+        encoder.write_tag(Tag::ImageDescription, description.join("\n").as_str())
+    }
+
+    fn save_png(&self, path: &Path) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), self.width(), self.height());
+        let data = match self.img_type() {
+            ImgType::Raw8 | ImgType::Y8 => {
+                encoder.set_color(png::ColorType::Grayscale);
+                encoder.set_depth(png::BitDepth::Eight);
+                self.as_u8().to_vec()
+            }
+            // PNG stores 16 bit data in big endian.
+            ImgType::Raw16 => {
+                encoder.set_color(png::ColorType::Grayscale);
+                encoder.set_depth(png::BitDepth::Sixteen);
+                self.as_u16().unwrap().iter().flat_map(|value| value.to_be_bytes()).collect()
+            }
+            ImgType::Rgb24 => {
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                self.to_rgb()
+            }
+        };
+
+        if let Some(metadata) = self.metadata() {
+            encoder.add_text_chunk("Source".into(), ascii(&metadata.camera_name)).map_err(io::Error::other)?;
+            encoder.add_text_chunk("Creation Time".into(), metadata.start.utc_string()).map_err(io::Error::other)?;
+        }
+        // The camera and date are already written with the standard PNG keywords.
+        for (key, value) in self.text_metadata().into_iter().filter(|(key, _)| !matches!(*key, "Camera" | "Date")) {
+            encoder.add_text_chunk(key.into(), value).map_err(io::Error::other)?;
+        }
+
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&data).map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)
+    }
+
+    /// Capture values as key and text, they only contain ASCII.
+    fn text_metadata(&self) -> Vec<(&'static str, String)> {
+        let Some(metadata) = self.metadata() else {
+            return Vec::new();
+        };
+        let mut text = vec![("Camera", ascii(&metadata.camera_name)), ("Date", metadata.start.utc_string())];
+        if let Some(exposure) = metadata.exposure {
+            text.push(("Exposure", format!("{} s", exposure.as_secs_f64())));
+        }
+        if let Some(gain) = metadata.gain {
+            text.push(("Gain", gain.to_string()));
+        }
+        if let Some(offset) = metadata.offset {
+            text.push(("Offset", offset.to_string()));
+        }
+        if let Some(temperature) = metadata.temperature {
+            text.push(("Temperature", format!("{:.1} C", temperature.0)));
+        }
+        if let Some(serial_number) = &metadata.serial_number {
+            text.push(("Serial Number", ascii(serial_number)));
+        }
+        text
+    }
+
+    /// The pixels of an Rgb24 frame in red, green, blue order.
+    fn to_rgb(&self) -> Vec<u8> {
+        self.as_u8().chunks_exact(3).flat_map(|bgr| [bgr[2], bgr[1], bgr[0]]).collect()
+    }
+}
+
+/// Replace characters that TIFF, PNG and FITS text can't hold, eg. from lossily decoded names.
+pub(crate) fn ascii(text: &str) -> String {
+    text.chars().map(|c| if c.is_ascii() { c } else { '?' }).collect()
+}
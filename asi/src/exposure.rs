@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Camera, Error, ExposureStatus, Frame, Result};
+
+/// How often a failed exposure is started before giving up.
+const MAX_ATTEMPTS: u32 = 3;
+/// Longest sleep between two status polls, so cancellation stays responsive.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// First poll interval once the exposure time has passed, doubled up to ```MAX_READOUT_POLL_INTERVAL```.
+const MIN_READOUT_POLL_INTERVAL: Duration = Duration::from_millis(1);
+const MAX_READOUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Token to cancel a running exposure, eg. from a UI thread.
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Camera {
+    /// Take a single exposure and download it, blocking until it's done.
+    ///
+    /// ```timeout``` limits the whole call including readout and retries, ```Duration::MAX``` waits forever.
+    /// A failed exposure is restarted up to 3 times in total.
+    pub fn expose(&self, duration: Duration, is_dark: bool, timeout: Duration) -> Result<Frame> {
+        self.expose_cancellable(duration, is_dark, timeout, &CancelToken::new())
+    }
+
+    /// Like ```expose```, but the exposure is stopped and ```Error::Cancelled``` is returned
+    /// when the token is cancelled.
+    pub fn expose_cancellable(&self, duration: Duration, is_dark: bool, timeout: Duration, cancel: &CancelToken) -> Result<Frame> {
+        self.expose_with(duration, is_dark, timeout, cancel, Self::exposure_frame)
+    }
+
+    /// Take an exposure and get its data with ```download```.
+    pub(crate) fn expose_with<T>(&self, duration: Duration, is_dark: bool, timeout: Duration, cancel: &CancelToken, download: impl Fn(&Self) -> Result<T>) -> Result<T> {
+        // A timeout too long for an Instant is no deadline.
+        let deadline = Instant::now().checked_add(timeout);
+        self.set_exposure(duration)?;
+
+        for _ in 0..MAX_ATTEMPTS {
+            if self.wait_for_exposure(duration, is_dark, deadline, timeout, cancel)? {
+                return download(self);
+            }
+        }
+
+        Err(Error::ExposureFailed { attempts: MAX_ATTEMPTS })
+    }
+
+    /// Start an exposure and wait until it's finished.
+    /// Returns ```false``` if the camera reported that the exposure failed.
+    fn wait_for_exposure(&self, duration: Duration, is_dark: bool, deadline: Option<Instant>, timeout: Duration, cancel: &CancelToken) -> Result<bool> {
+        let started = Instant::now();
+        self.start_exposure(is_dark)?;
+
+        let result = self.poll_exposure(duration, started, deadline, timeout, cancel);
+        if result.is_err() {
+            // Don't leave the camera exposing, but the caller needs the original error.
+            let _ = self.stop_exposure();
+        }
+        result
+    }
+
+    fn poll_exposure(&self, duration: Duration, started: Instant, deadline: Option<Instant>, timeout: Duration, cancel: &CancelToken) -> Result<bool> {
+        let mut readout_interval = MIN_READOUT_POLL_INTERVAL;
+        loop {
+            if cancel.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+
+            match self.exposure_status()? {
+                ExposureStatus::Success => return Ok(true),
+                ExposureStatus::Failed => return Ok(false),
+                ExposureStatus::Idle | ExposureStatus::Working => {}
+            }
+
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                return Err(Error::ExposureTimeout { timeout });
+            }
+
+            // Sleep through the exposure, then poll quickly for the readout.
+            let remaining = duration.saturating_sub(started.elapsed());
+            let interval = if remaining.is_zero() {
+                let interval = readout_interval;
+                readout_interval = (readout_interval * 2).min(MAX_READOUT_POLL_INTERVAL);
+                interval
+            } else {
+                remaining.min(MAX_POLL_INTERVAL)
+            };
+            thread::sleep(deadline.map_or(interval, |deadline| interval.min(deadline - now)));
+        }
+    }
+}
@@ -0,0 +1,493 @@
+//! Reading and writing frames as FITS images.
+//!
+//! Raw8, Y8 and Raw16 frames are written as 2D images, Rgb24 frames as 3 planes in red, green, blue order.
+//! Raw16 uses ```BZERO = 32768``` to store unsigned values, as usual for cameras.
+//! The bit depth of the camera is kept in the ```BITDEPTH``` card.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::export::ascii;
+use crate::{BayerPattern, CameraInfo, Frame, FrameFormat, ImgType};
+
+/// FITS files are made of blocks of this size.
+const BLOCK_SIZE: usize = 2880;
+const CARD_SIZE: usize = 80;
+/// Offset of unsigned 16 bit data stored as signed.
+const BZERO_16: i64 = 32768;
+/// Keywords which describe the data, they're only written by ```FitsWriter``` itself.
+const STRUCTURAL_KEYWORDS: [&str; 6] = ["SIMPLE", "BITPIX", "NAXIS", "BZERO", "BSCALE", "END"];
+
+/// Value of a header card.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Logical(bool),
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    /// Formatted as the value field of a fixed format card.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Logical(value) => write!(f, "{:>20}", if *value { "T" } else { "F" }),
+            Self::Integer(value) => write!(f, "{:>20}", value),
+            Self::Real(value) => {
+                // Keep the decimal point, so it's read back as a real.
+                let mut real = match value.fract() == 0.0 && value.abs() < 1e15 {
+                    true => format!("{:.1}", value),
+                    false => value.to_string(),
+                };
+                if real.len() > 20 {
+                    real = format!("{:E}", value);
+                }
+                write!(f, "{:>20}", real)
+            }
+            // Strings are at least 8 characters long, quotes are doubled.
+            Self::Text(value) => write!(f, "'{:<8}'", value.replace('\'', "''")),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Logical(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Real(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+/// A keyword with its value and comment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    pub keyword: String,
+    pub value: Value,
+    pub comment: String,
+}
+
+impl Card {
+    pub fn new(keyword: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self { keyword: keyword.into(), value: value.into(), comment: String::new() }
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Keywords have up to 8 characters of uppercase letters, digits, hyphens and underscores.
+    fn is_valid_keyword(keyword: &str) -> bool {
+        !keyword.is_empty()
+            && keyword.len() <= 8
+            && keyword.bytes().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == b'-' || c == b'_')
+    }
+
+    /// The 80 characters of the card, a long comment is cut off.
+    fn to_record(&self) -> io::Result<String> {
+        if !Self::is_valid_keyword(&self.keyword) {
+            return Err(invalid_input(format!("invalid FITS keyword {:?}", self.keyword)));
+        }
+        // FITS has no notation for them.
+        if let Value::Real(value) = self.value {
+            if !value.is_finite() {
+                return Err(invalid_input(format!("value {} of FITS card {} isn't finite", value, self.keyword)));
+            }
+        }
+        let mut record = format!("{:<8}= {}", self.keyword, self.value);
+        if !self.comment.is_empty() {
+            record.push_str(" / ");
+            record.push_str(&self.comment);
+        }
+        if !record.is_ascii() {
+            return Err(invalid_input(format!("FITS card {} isn't ASCII", self.keyword)));
+        }
+        if record.len() > CARD_SIZE {
+            if let Value::Text(_) = self.value {
+                if record.len() - self.comment.len() > CARD_SIZE {
+                    return Err(invalid_input(format!("value of FITS card {} is too long", self.keyword)));
+                }
+            }
+            record.truncate(CARD_SIZE);
+        }
+        Ok(format!("{:<80}", record))
+    }
+
+    /// Parse a record, ```None``` for records without a value like ```COMMENT```.
+    fn from_record(record: &str) -> io::Result<Option<Self>> {
+        let keyword = record[..8].trim_end();
+        if record.get(8..10) != Some("= ") {
+            return Ok(None);
+        }
+        let field = record[10..].trim_start();
+
+        let (value, comment) = if let Some(text) = field.strip_prefix('\'') {
+            let mut value = String::new();
+            let mut chars = text.char_indices().peekable();
+            let end = loop {
+                match chars.next() {
+                    Some((index, '\'')) => {
+                        if let Some((_, '\'')) = chars.peek() {
+                            chars.next();
+                            value.push('\'');
+                        } else {
+                            break index + 1;
+                        }
+                    }
+                    Some((_, c)) => value.push(c),
+                    None => return Err(invalid_data(format!("unterminated string in FITS card {}", keyword))),
+                }
+            };
+            let comment = text[end..].trim_start().strip_prefix('/').unwrap_or("");
+            // Trailing spaces of strings aren't significant.
+            (Value::Text(value.trim_end().to_string()), comment)
+        } else {
+            let (raw, comment) = field.split_once('/').unwrap_or((field, ""));
+            let raw = raw.trim();
+            let value = match raw {
+                "T" => Value::Logical(true),
+                "F" => Value::Logical(false),
+                _ => match raw.parse() {
+                    Ok(integer) => Value::Integer(integer),
+                    Err(_) => Value::Real(raw.replace('D', "E").parse().ok().filter(|real: &f64| real.is_finite()).ok_or_else(|| {
+                        invalid_data(format!("invalid value {:?} of FITS card {}", raw, keyword))
+                    })?),
+                },
+            };
+            (value, comment)
+        };
+
+        Ok(Some(Self { keyword: keyword.to_string(), value, comment: comment.trim().to_string() }))
+    }
+}
+
+/// Writes frames as FITS files with keywords from the frame metadata, the camera and the user.
+#[derive(Debug, Clone, Default)]
+pub struct FitsWriter {
+    camera: Option<CameraInfo>,
+    cards: Vec<Card>,
+}
+
+impl FitsWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add ```XPIXSZ``` and ```YPIXSZ``` from the camera, ```INSTRUME``` is also taken from the frame metadata.
+    pub fn camera(mut self, info: &CameraInfo) -> Self {
+        self.camera = Some(info.clone());
+        self
+    }
+
+    /// Add a card, it replaces a generated card with the same keyword.
+    /// Structural keywords like ```BITPIX``` or ```NAXIS1``` can't be set, ```write``` fails for them
+    /// and for reals which are NaN or infinite.
+    pub fn card(mut self, card: Card) -> Self {
+        self.cards.push(card);
+        self
+    }
+
+    /// All cards which are written for the frame, without the structural ones like ```BITPIX```.
+    pub fn cards(&self, frame: &Frame) -> Vec<Card> {
+        let mut cards = vec![Card::new("ROWORDER", "TOP-DOWN").comment("order of the rows")];
+        cards.push(Card::new("XBINNING", frame.bin()).comment("binning factor"));
+        cards.push(Card::new("YBINNING", frame.bin()).comment("binning factor"));
+
+        if let Some(info) = &self.camera {
+            cards.push(Card::new("INSTRUME", ascii(&info.name)).comment("camera"));
+            let pixel_size = f64::from(info.pixel_size) * f64::from(frame.bin());
+            cards.push(Card::new("XPIXSZ", pixel_size).comment("[um] pixel size including binning"));
+            cards.push(Card::new("YPIXSZ", pixel_size).comment("[um] pixel size including binning"));
+        }
+        if let Some(bayer_pattern) = frame.bayer_pattern() {
+            cards.push(Card::new("BAYERPAT", bayer_pattern_name(bayer_pattern)).comment("color filter array"));
+        }
+        cards.push(Card::new("BITDEPTH", frame.bit_depth()).comment("[bit] ADC bit depth, 16 bit data is left-aligned"));
+
+        if let Some(metadata) = frame.metadata().filter(|_| self.camera.is_none()) {
+            cards.push(Card::new("INSTRUME", ascii(&metadata.camera_name)).comment("camera"));
+        }
+        if let Some(metadata) = frame.metadata() {
+            cards.push(Card::new("DATE-OBS", metadata.start.utc_string()).comment("UTC start of exposure"));
+            if let Some(exposure) = metadata.exposure {
+                cards.push(Card::new("EXPTIME", exposure.as_secs_f64()).comment("[s] exposure time"));
+            }
+            if let Some(gain) = metadata.gain {
+                cards.push(Card::new("GAIN", gain).comment("sensor gain"));
+            }
+            if let Some(offset) = metadata.offset {
+                cards.push(Card::new("OFFSET", offset).comment("sensor offset"));
+            }
+            if let Some(temperature) = metadata.temperature {
+                cards.push(Card::new("CCD-TEMP", temperature.0).comment("[C] sensor temperature"));
+            }
+            if let Some(temperature) = metadata.target_temperature {
+                cards.push(Card::new("SET-TEMP", temperature.0).comment("[C] cooler target temperature"));
+            }
+            cards.push(Card::new("XORGSUBF", metadata.roi.start_x).comment("subframe origin"));
+            cards.push(Card::new("YORGSUBF", metadata.roi.start_y).comment("subframe origin"));
+            if let Some(serial_number) = &metadata.serial_number {
+                cards.push(Card::new("CAMERASN", serial_number.as_str()).comment("camera serial number"));
+            }
+            cards.push(Card::new("FRAMENUM", metadata.sequence as i64).comment("sequence number"));
+        }
+
+        for card in &self.cards {
+            match cards.iter_mut().find(|generated| generated.keyword == card.keyword) {
+                Some(generated) => *generated = card.clone(),
+                None => cards.push(card.clone()),
+            }
+        }
+        cards
+    }
+
+    pub fn write(&self, frame: &Frame, writer: impl Write) -> io::Result<()> {
+        if let Some(card) = self.cards.iter().find(|card| is_structural(&card.keyword)) {
+            return Err(invalid_input(format!("FITS card {} is written by FitsWriter itself", card.keyword)));
+        }
+        let mut writer = BufWriter::new(writer);
+        let format = frame.format();
+
+        let mut cards = vec![Card::new("SIMPLE", true).comment("conforms to FITS standard")];
+        match format.img_type {
+            ImgType::Raw8 | ImgType::Y8 => {
+                cards.push(Card::new("BITPIX", 8));
+                cards.push(Card::new("NAXIS", 2));
+            }
+            ImgType::Raw16 => {
+                cards.push(Card::new("BITPIX", 16));
+                cards.push(Card::new("NAXIS", 2));
+            }
+            ImgType::Rgb24 => {
+                cards.push(Card::new("BITPIX", 8));
+                cards.push(Card::new("NAXIS", 3));
+            }
+        }
+        cards.push(Card::new("NAXIS1", format.width));
+        cards.push(Card::new("NAXIS2", format.height));
+        if format.img_type == ImgType::Rgb24 {
+            cards.push(Card::new("NAXIS3", 3));
+        }
+        if format.img_type == ImgType::Raw16 {
+            cards.push(Card::new("BZERO", BZERO_16).comment("offset for unsigned data"));
+            cards.push(Card::new("BSCALE", 1));
+        }
+        cards.extend(self.cards(frame));
+
+        let mut header = String::new();
+        for card in &cards {
+            header.push_str(&card.to_record()?);
+        }
+        header.push_str(&format!("{:<80}", "END"));
+        writer.write_all(header.as_bytes())?;
+        write_padding(&mut writer, header.len(), b' ')?;
+
+        match format.img_type {
+            ImgType::Raw8 | ImgType::Y8 => writer.write_all(frame.as_u8())?,
+            ImgType::Raw16 => {
+                for &value in frame.as_u16().unwrap() {
+                    writer.write_all(&(value ^ 0x8000).to_be_bytes())?;
+                }
+            }
+            // Interleaved blue, green, red pixels become red, green and blue planes.
+            ImgType::Rgb24 => {
+                for channel in [2, 1, 0] {
+                    let plane: Vec<u8> = frame.as_u8().iter().skip(channel).step_by(3).copied().collect();
+                    writer.write_all(&plane)?;
+                }
+            }
+        }
+        write_padding(&mut writer, format.buffer_size(), 0)?;
+        writer.flush()
+    }
+
+    pub fn save(&self, frame: &Frame, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write(frame, File::create(path)?)
+    }
+}
+
+/// A FITS image read by ```read```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitsImage {
+    /// The frame without capture metadata, 8 bit images are read as Raw8.
+    pub frame: Frame,
+    /// All cards of the header in file order.
+    pub cards: Vec<Card>,
+}
+
+impl FitsImage {
+    pub fn card(&self, keyword: &str) -> Option<&Card> {
+        self.cards.iter().find(|card| card.keyword == keyword)
+    }
+
+    pub fn value(&self, keyword: &str) -> Option<&Value> {
+        self.card(keyword).map(|card| &card.value)
+    }
+}
+
+/// Read the primary image of a FITS file written by ```FitsWriter``` or another program.
+///
+/// Only 8 bit and 16 bit images are supported, 16 bit data has to use ```BZERO = 32768```.
+/// Without a ```BITDEPTH``` card the bit depth is the one of the data.
+pub fn read(reader: impl Read) -> io::Result<FitsImage> {
+    let mut reader = BufReader::new(reader);
+    let mut cards = Vec::new();
+    let mut block = [0; BLOCK_SIZE];
+
+    'header: loop {
+        reader.read_exact(&mut block)?;
+        for record in block.chunks(CARD_SIZE) {
+            let record = std::str::from_utf8(record)
+                .ok()
+                .filter(|record| record.is_ascii())
+                .ok_or_else(|| invalid_data("FITS header isn't ASCII"))?;
+            if record.trim_end() == "END" {
+                break 'header;
+            }
+            if let Some(card) = Card::from_record(record)? {
+                cards.push(card);
+            }
+        }
+    }
+
+    let integer = |keyword: &str| {
+        cards.iter().find(|card| card.keyword == keyword).and_then(|card| match card.value {
+            Value::Integer(value) => Some(value),
+            _ => None,
+        })
+    };
+    let required = |keyword: &str| integer(keyword).ok_or_else(|| invalid_data(format!("FITS header has no {}", keyword)));
+
+    let bitpix = required("BITPIX")?;
+    let naxis = required("NAXIS")?;
+    let width = u32::try_from(required("NAXIS1")?).map_err(|_| invalid_data("invalid NAXIS1"))?;
+    let height = u32::try_from(required("NAXIS2")?).map_err(|_| invalid_data("invalid NAXIS2"))?;
+    let img_type = match (bitpix, naxis, integer("NAXIS3")) {
+        (8, 2, _) => ImgType::Raw8,
+        (8, 3, Some(3)) => ImgType::Rgb24,
+        (16, 2, _) if integer("BZERO") == Some(BZERO_16) => ImgType::Raw16,
+        _ => return Err(invalid_data(format!("unsupported FITS image with BITPIX {} and NAXIS {}", bitpix, naxis))),
+    };
+    let bayer_pattern = match cards.iter().find(|card| card.keyword == "BAYERPAT").map(|card| &card.value) {
+        Some(Value::Text(name)) => bayer_pattern_from_name(name),
+        _ => None,
+    };
+    let data_bits = if img_type == ImgType::Raw16 { 16 } else { 8 };
+    let format = FrameFormat {
+        width,
+        height,
+        bin: integer("XBINNING").and_then(|bin| u32::try_from(bin).ok()).unwrap_or(1),
+        img_type,
+        bayer_pattern: bayer_pattern.filter(|_| img_type != ImgType::Rgb24),
+        bit_depth: integer("BITDEPTH").and_then(|bits| u32::try_from(bits).ok()).filter(|bits| (1..=16).contains(bits)).unwrap_or(data_bits),
+    };
+
+    // The size comes from the header, so the data is only allocated as far as the input really has it.
+    let size = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(img_type.bytes_per_pixel()))
+        .ok_or_else(|| invalid_data(format!("FITS image of {}x{} pixels is too big", width, height)))?;
+    let mut data = Vec::new();
+    reader.by_ref().take(size as u64).read_to_end(&mut data)?;
+    if data.len() < size {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "FITS data is shorter than the image"));
+    }
+    let mut frame = Frame::new(format);
+    match img_type {
+        ImgType::Raw8 | ImgType::Y8 => frame.as_u8_mut().copy_from_slice(&data),
+        ImgType::Raw16 => {
+            for (value, bytes) in frame.as_u16_mut().unwrap().iter_mut().zip(data.chunks_exact(2)) {
+                *value = u16::from_be_bytes([bytes[0], bytes[1]]) ^ 0x8000;
+            }
+        }
+        ImgType::Rgb24 => {
+            let plane_size = width as usize * height as usize;
+            let buffer = frame.as_u8_mut();
+            for (plane, channel) in data.chunks_exact(plane_size).zip([2, 1, 0]) {
+                for (pixel, &value) in plane.iter().enumerate() {
+                    buffer[pixel * 3 + channel] = value;
+                }
+            }
+        }
+    }
+
+    Ok(FitsImage { frame, cards })
+}
+
+pub fn open(path: impl AsRef<Path>) -> io::Result<FitsImage> {
+    read(File::open(path)?)
+}
+
+/// ```NAXIS``` followed by a number is structural too.
+fn is_structural(keyword: &str) -> bool {
+    STRUCTURAL_KEYWORDS.contains(&keyword)
+        || keyword.strip_prefix("NAXIS").is_some_and(|axis| !axis.is_empty() && axis.bytes().all(|c| c.is_ascii_digit()))
+}
+
+fn write_padding(writer: &mut impl Write, written: usize, fill: u8) -> io::Result<()> {
+    let padding = (BLOCK_SIZE - written % BLOCK_SIZE) % BLOCK_SIZE;
+    writer.write_all(&vec![fill; padding])
+}
+
+fn bayer_pattern_name(bayer_pattern: BayerPattern) -> &'static str {
+    match bayer_pattern {
+        BayerPattern::Rg => "RGGB",
+        BayerPattern::Bg => "BGGR",
+        BayerPattern::Gr => "GRBG",
+        BayerPattern::Gb => "GBRG",
+    }
+}
+
+fn bayer_pattern_from_name(name: &str) -> Option<BayerPattern> {
+    match name {
+        "RGGB" => Some(BayerPattern::Rg),
+        "BGGR" => Some(BayerPattern::Bg),
+        "GRBG" => Some(BayerPattern::Gr),
+        "GBRG" => Some(BayerPattern::Gb),
+        _ => None,
+    }
+}
+
+fn invalid_input(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
@@ -0,0 +1,241 @@
+use crate::debayer::effective_pattern;
+use crate::metadata::{video_timestamps, FrameControls};
+use crate::{BayerPattern, Camera, ControlType, FlipStatus, FrameMetadata, ImgType, Result, Roi, Timestamp};
+
+/// Geometry and pixel format of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameFormat {
+    /// Width after binning.
+    pub width: u32,
+    /// Height after binning.
+    pub height: u32,
+    pub bin: u32,
+    pub img_type: ImgType,
+    /// Only set for raw data of a color camera.
+    /// It's the pattern at the first pixel of the frame, so it includes the shift by the ROI start position and the flip.
+    pub bayer_pattern: Option<BayerPattern>,
+    /// ADC bit depth of the camera, Raw16 data is left-aligned in 16 bits.
+    pub bit_depth: u32,
+}
+
+impl FrameFormat {
+    /// Size of the frame data in bytes.
+    pub fn buffer_size(&self) -> usize {
+        self.width as usize * self.height as usize * self.img_type.bytes_per_pixel()
+    }
+}
+
+/// A single image with its own correctly sized buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Stored as ```u16``` so Raw16 data can be viewed without copying.
+    buffer: Vec<u16>,
+    format: FrameFormat,
+    metadata: Option<FrameMetadata>,
+}
+
+/// Value of a single pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pixel {
+    /// Raw8 or Y8.
+    Mono8(u8),
+    /// Raw16.
+    Mono16(u16),
+    /// Rgb24, already in red, green, blue order.
+    Rgb([u8; 3]),
+}
+
+impl Frame {
+    /// Allocate a zeroed frame for the format.
+    pub fn new(format: FrameFormat) -> Self {
+        Self { buffer: vec![0; format.buffer_size().div_ceil(2)], format, metadata: None }
+    }
+
+    /// Create a frame from existing data.
+    /// Raw16 data has to be in native endianness. Returns ```None``` if the length doesn't match the format.
+    pub fn from_bytes(format: FrameFormat, data: &[u8]) -> Option<Self> {
+        if data.len() != format.buffer_size() {
+            return None;
+        }
+        let mut frame = Self::new(format);
+        frame.as_u8_mut().copy_from_slice(data);
+        Some(frame)
+    }
+
+    pub fn format(&self) -> &FrameFormat {
+        &self.format
+    }
+
+    pub fn width(&self) -> u32 {
+        self.format.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.format.height
+    }
+
+    pub fn bin(&self) -> u32 {
+        self.format.bin
+    }
+
+    pub fn img_type(&self) -> ImgType {
+        self.format.img_type
+    }
+
+    pub fn bayer_pattern(&self) -> Option<BayerPattern> {
+        self.format.bayer_pattern
+    }
+
+    pub fn bit_depth(&self) -> u32 {
+        self.format.bit_depth
+    }
+
+    /// Capture metadata, set for every frame captured from a camera.
+    pub fn metadata(&self) -> Option<&FrameMetadata> {
+        self.metadata.as_ref()
+    }
+
+    pub fn set_metadata(&mut self, metadata: Option<FrameMetadata>) {
+        self.metadata = metadata;
+    }
+
+    /// The raw bytes of the frame. Raw16 data is in native endianness, Rgb24 in blue, green, red order.
+    pub fn as_u8(&self) -> &[u8] {
+        // The u16 buffer is at least as big as the data and u8 has no alignment requirements.
+        unsafe {std::slice::from_raw_parts(self.buffer.as_ptr() as *const u8, self.format.buffer_size())}
+    }
+
+    pub fn as_u8_mut(&mut self) -> &mut [u8] {
+        unsafe {std::slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut u8, self.format.buffer_size())}
+    }
+
+    /// The pixels of a Raw16 frame, ```None``` for other formats.
+    pub fn as_u16(&self) -> Option<&[u16]> {
+        match self.format.img_type {
+            ImgType::Raw16 => Some(&self.buffer),
+            _ => None,
+        }
+    }
+
+    pub fn as_u16_mut(&mut self) -> Option<&mut [u16]> {
+        match self.format.img_type {
+            ImgType::Raw16 => Some(&mut self.buffer),
+            _ => None,
+        }
+    }
+
+    /// Get the pixel at the position, ```None``` if it's outside of the frame.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Pixel> {
+        if x >= self.format.width || y >= self.format.height {
+            return None;
+        }
+        let index = y as usize * self.format.width as usize + x as usize;
+        let pixel = match self.format.img_type {
+            ImgType::Raw8 | ImgType::Y8 => Pixel::Mono8(self.as_u8()[index]),
+            ImgType::Raw16 => Pixel::Mono16(self.buffer[index]),
+            ImgType::Rgb24 => {
+                let bgr = &self.as_u8()[index * 3..index * 3 + 3];
+                Pixel::Rgb([bgr[2], bgr[1], bgr[0]])
+            }
+        };
+        Some(pixel)
+    }
+
+    /// Change the format, the buffer is only reallocated if the size changes.
+    /// The data isn't converted, so it's only meaningful until the frame is filled again.
+    pub fn reformat(&mut self, format: FrameFormat) {
+        if format != self.format {
+            self.buffer.resize(format.buffer_size().div_ceil(2), 0);
+            self.format = format;
+        }
+    }
+
+    /// The SDK delivers Raw16 data in little endian.
+    pub(crate) fn fix_sdk_endianness(&mut self) {
+        if cfg!(target_endian = "big") && self.format.img_type == ImgType::Raw16 {
+            self.buffer.iter_mut().for_each(|value| *value = u16::from_le(*value));
+        }
+    }
+}
+
+impl Camera {
+    /// Format of the frames which are captured with the current ROI.
+    pub fn frame_format(&self) -> Result<FrameFormat> {
+        self.roi_frame_format(&self.roi()?)
+    }
+
+    /// Format of the frames which are captured with ```roi```, only the flip of color cameras is read.
+    pub(crate) fn roi_frame_format(&self, roi: &Roi) -> Result<FrameFormat> {
+        let is_raw = matches!(roi.img_type, ImgType::Raw8 | ImgType::Raw16);
+        let bayer_pattern = match self.info().is_color_cam && is_raw {
+            true => {
+                let flip = if self.supports(ControlType::Flip) { self.flip()? } else { FlipStatus::None };
+                Some(effective_pattern(self.info().bayer_pattern, roi.start_x, roi.start_y, roi.width, roi.height, flip))
+            }
+            false => None,
+        };
+        Ok(FrameFormat {
+            width: roi.width,
+            height: roi.height,
+            bin: roi.bin,
+            img_type: roi.img_type,
+            bayer_pattern,
+            bit_depth: self.info().bit_depth,
+        })
+    }
+
+    /// Allocate a frame for the current ROI.
+    pub fn new_frame(&self) -> Result<Frame> {
+        Ok(Frame::new(self.frame_format()?))
+    }
+
+    /// Get the data of a finished exposure as a frame.
+    pub fn exposure_frame(&self) -> Result<Frame> {
+        let mut frame = self.new_frame()?;
+        self.exposure_frame_into(&mut frame)?;
+        Ok(frame)
+    }
+
+    /// Get the data of a finished exposure into an existing frame, it's resized to the current ROI if needed.
+    pub fn exposure_frame_into(&self, frame: &mut Frame) -> Result<()> {
+        let _format = self.lock_format();
+        // Everything for the metadata is read before the download, so a failing read doesn't lose the exposure.
+        let roi = self.roi()?;
+        frame.reformat(self.roi_frame_format(&roi)?);
+        let controls = self.frame_controls();
+        // The frame was just sized for the current ROI.
+        self.get_data_after_exposure_unchecked(frame.as_u8_mut())?;
+        let (start, end) = self.exposure_timestamps(Timestamp::now(), controls.exposure);
+        frame.fix_sdk_endianness();
+        frame.metadata = Some(self.frame_metadata(start, end, roi, controls));
+        Ok(())
+    }
+
+    /// Get the next frame of the video capture, waits ```wait_ms``` for it.
+    pub fn video_frame(&self, wait_ms: u32) -> Result<Frame> {
+        let mut frame = self.new_frame()?;
+        self.video_frame_into(&mut frame, wait_ms)?;
+        Ok(frame)
+    }
+
+    /// Get the next frame of the video capture into an existing frame, it's resized to the current ROI if needed.
+    pub fn video_frame_into(&self, frame: &mut Frame, wait_ms: u32) -> Result<()> {
+        let _format = self.lock_format();
+        // Everything for the metadata is read before the download, so a failing read doesn't lose the frame.
+        let roi = self.roi()?;
+        let format = self.roi_frame_format(&roi)?;
+        self.video_frame_with(frame, wait_ms, format, roi, self.frame_controls())
+    }
+
+    /// Like ```video_frame_into```, but with the ROI, its format and the controls read before.
+    /// The caller either owns the camera or holds the format lock, so the ROI can't change in the meantime.
+    pub(crate) fn video_frame_with(&self, frame: &mut Frame, wait_ms: u32, format: FrameFormat, roi: Roi, controls: FrameControls) -> Result<()> {
+        frame.reformat(format);
+        // The caller makes sure that the format still matches the ROI.
+        self.get_video_data_unchecked(frame.as_u8_mut(), wait_ms)?;
+        let (start, end) = video_timestamps(Timestamp::now(), controls.exposure);
+        frame.fix_sdk_endianness();
+        frame.metadata = Some(self.frame_metadata(start, end, roi, controls));
+        Ok(())
+    }
+}
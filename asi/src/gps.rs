@@ -0,0 +1,169 @@
+use std::fmt;
+use std::time::Duration;
+
+use asi_sys::*;
+use serde::{Deserialize, Serialize};
+
+use crate::metadata::{video_timestamps, FrameControls};
+use crate::{Camera, CancelToken, Frame, Result, Roi, Timestamp};
+
+/// UTC date and time from the GPS receiver.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct GpsDateTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// Fraction of the second, the receiver has a resolution of 100ns.
+    pub nanosecond: u32,
+}
+
+impl GpsDateTime {
+    pub fn microsecond(&self) -> u32 {
+        self.nanosecond / 1000
+    }
+}
+
+impl From<ASI_DATE_TIME> for GpsDateTime {
+    fn from(datetime: ASI_DATE_TIME) -> Self {
+        Self {
+            year: datetime.Year,
+            month: datetime.Month.max(0) as u32,
+            day: datetime.Day.max(0) as u32,
+            hour: datetime.Hour.max(0) as u32,
+            minute: datetime.Minute.max(0) as u32,
+            second: datetime.Second.max(0) as u32,
+            // Usecond counts in 0.1us. Invalid GPS data can have any value, so both are clamped to their range.
+            nanosecond: datetime.Msecond.clamp(0, 999) as u32 * 1_000_000 + datetime.Usecond.clamp(0, 9999) as u32 * 100,
+        }
+    }
+}
+
+/// ISO 8601 format with 0.1us, eg. ```2024-03-01T20:44:13.2500000```.
+impl fmt::Display for GpsDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:07}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            self.nanosecond / 100,
+        )
+    }
+}
+
+/// GPS data which the camera recorded for a line of a frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GpsData {
+    pub datetime: GpsDateTime,
+    /// Positive for north.
+    pub latitude: f64,
+    /// Positive for east.
+    pub longitude: f64,
+    /// Altitude in meters.
+    pub altitude: f64,
+    /// Number of satellites in use.
+    pub satellites: u32,
+}
+
+impl From<ASI_GPS_DATA> for GpsData {
+    fn from(data: ASI_GPS_DATA) -> Self {
+        Self {
+            datetime: data.Datetime.into(),
+            latitude: data.Latitude,
+            longitude: data.Longitude,
+            // The SDK reports it in 0.1m.
+            altitude: data.Altitude as f64 / 10.0,
+            satellites: data.SatelliteNum.max(0) as u32,
+        }
+    }
+}
+
+impl Camera {
+    /// Like ```get_video_data```, but also returns the GPS data of the frame.
+    pub fn get_video_data_gps(&self, buffer: &mut [u8], wait_ms: u32) -> Result<GpsData> {
+        let _format = self.lock_format();
+        self.check_buffer_size(buffer)?;
+        self.get_video_data_gps_unchecked(buffer, wait_ms)
+    }
+
+    pub(crate) fn get_video_data_gps_unchecked(&self, buffer: &mut [u8], wait_ms: u32) -> Result<GpsData> {
+        let mut gps_data = ASI_GPS_DATA::default();
+        let status = unsafe {ASIGetVideoDataGPS(self.camera_id.into(), buffer.as_mut_ptr(), (buffer.len() as i32).into(), wait_ms as i32, &mut gps_data)};
+        self.check_args(status, "ASIGetVideoDataGPS", || format!("buffer size: {}, wait: {}ms", buffer.len(), wait_ms))?;
+        Ok(gps_data.into())
+    }
+
+    /// Like ```get_data_after_exposure```, but also returns the GPS data of the frame.
+    pub fn get_data_after_exposure_gps(&self, buffer: &mut [u8]) -> Result<GpsData> {
+        let _format = self.lock_format();
+        self.check_buffer_size(buffer)?;
+        self.get_data_after_exposure_gps_unchecked(buffer)
+    }
+
+    pub(crate) fn get_data_after_exposure_gps_unchecked(&self, buffer: &mut [u8]) -> Result<GpsData> {
+        let mut gps_data = ASI_GPS_DATA::default();
+        let status = unsafe {ASIGetDataAfterExpGPS(self.camera_id.into(), buffer.as_mut_ptr(), (buffer.len() as i32).into(), &mut gps_data)};
+        self.check_args(status, "ASIGetDataAfterExpGPS", || format!("buffer size: {}", buffer.len()))?;
+        self.exposure_finished();
+        Ok(gps_data.into())
+    }
+
+    /// Get the GPS data of the start and the end line of the last frame.
+    /// The lines are set with ```set_gps_start_line``` and ```set_gps_end_line```.
+    pub fn gps_line_data(&self) -> Result<(GpsData, GpsData)> {
+        let (mut start_line, mut end_line) = (ASI_GPS_DATA::default(), ASI_GPS_DATA::default());
+        let status = unsafe {ASIGPSGetData(self.camera_id.into(), &mut start_line, &mut end_line)};
+        self.check(status, "ASIGPSGetData")?;
+        Ok((start_line.into(), end_line.into()))
+    }
+
+    /// Get the next frame of the video capture with its GPS data, waits ```wait_ms``` for it.
+    /// The GPS data is also in the metadata of the frame.
+    pub fn video_frame_with_gps(&self, wait_ms: u32) -> Result<(Frame, GpsData)> {
+        let _format = self.lock_format();
+        let (mut frame, roi, controls) = self.prepare_gps_frame()?;
+        // The frame was just sized for the current ROI.
+        let gps_data = self.get_video_data_gps_unchecked(frame.as_u8_mut(), wait_ms)?;
+        let (start, end) = video_timestamps(Timestamp::now(), controls.exposure);
+        self.finish_gps_frame(&mut frame, start, end, roi, controls, gps_data);
+        Ok((frame, gps_data))
+    }
+
+    /// Get the data of a finished exposure as a frame with its GPS data.
+    /// The GPS data is also in the metadata of the frame.
+    pub fn exposure_frame_with_gps(&self) -> Result<(Frame, GpsData)> {
+        let _format = self.lock_format();
+        let (mut frame, roi, controls) = self.prepare_gps_frame()?;
+        // The frame was just sized for the current ROI.
+        let gps_data = self.get_data_after_exposure_gps_unchecked(frame.as_u8_mut())?;
+        let (start, end) = self.exposure_timestamps(Timestamp::now(), controls.exposure);
+        self.finish_gps_frame(&mut frame, start, end, roi, controls, gps_data);
+        Ok((frame, gps_data))
+    }
+
+    /// Like ```expose```, but the frame is downloaded together with its GPS data.
+    pub fn expose_with_gps(&self, duration: Duration, is_dark: bool, timeout: Duration) -> Result<(Frame, GpsData)> {
+        self.expose_with(duration, is_dark, timeout, &CancelToken::new(), Self::exposure_frame_with_gps)
+    }
+
+    /// Everything for the metadata is read before the download, so a failing read doesn't lose the frame.
+    fn prepare_gps_frame(&self) -> Result<(Frame, Roi, FrameControls)> {
+        let roi = self.roi()?;
+        let frame = Frame::new(self.roi_frame_format(&roi)?);
+        Ok((frame, roi, self.frame_controls()))
+    }
+
+    fn finish_gps_frame(&self, frame: &mut Frame, start: Timestamp, end: Timestamp, roi: Roi, controls: FrameControls, gps_data: GpsData) {
+        frame.fix_sdk_endianness();
+        let mut metadata = self.frame_metadata(start, end, roi, controls);
+        metadata.gps = Some(gps_data);
+        frame.set_metadata(Some(metadata));
+    }
+}
@@ -1,8 +1,48 @@
-use std::ffi::CString;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::Duration;
 
 use asi_sys::*;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+mod adu;
+#[cfg(feature = "tokio")]
+mod async_camera;
+mod auto_exposure;
+mod controls;
+pub mod debayer;
+mod exposure;
+mod export;
+pub mod fits;
+mod frame;
+mod gps;
+mod metadata;
+mod roi;
+pub mod ser;
+mod settings;
+mod stats;
+mod video;
+mod worker;
+
+#[cfg(feature = "tokio")]
+pub use async_camera::*;
+pub use auto_exposure::*;
+pub use controls::*;
+pub use exposure::*;
+pub use frame::*;
+pub use gps::*;
+pub use metadata::*;
+pub use roi::*;
+pub use settings::*;
+pub use stats::*;
+pub use video::*;
+pub use worker::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BayerPattern {
     Rg,
     Bg,
@@ -10,20 +50,22 @@ pub enum BayerPattern {
     Gb,
 }
 
-impl From<u32> for BayerPattern {
-    fn from(value: u32) -> Self {
+impl TryFrom<u32> for BayerPattern {
+    type Error = UnknownValue;
+
+    fn try_from(value: u32) -> Result<Self, UnknownValue> {
         match value {
-            0 => Self::Rg,
-            1 => Self::Bg,
-            2 => Self::Gr,
-            3 => Self::Gb,
-            i => panic!("Invalid bayer pattern: {}", i),
+            0 => Ok(Self::Rg),
+            1 => Ok(Self::Bg),
+            2 => Ok(Self::Gr),
+            3 => Ok(Self::Gb),
+            i => Err(UnknownValue { kind: "bayer pattern", value: i.into() }),
         }
     }
 }
 
 /// Supported Video Format
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ImgType {
     Raw8,
     Rgb24,
@@ -31,20 +73,33 @@ pub enum ImgType {
     Y8,
 }
 
-impl From<i32> for ImgType {
-    fn from(img_type: i32) -> Self {
+impl TryFrom<i32> for ImgType {
+    type Error = UnknownValue;
+
+    fn try_from(img_type: i32) -> Result<Self, UnknownValue> {
         match img_type {
-            0 => Self::Raw8,
-            1 => Self::Rgb24,
-            2 => Self::Raw16,
-            3 => Self::Y8,
-            i => panic!("Invalid image type: {}", i),
+            0 => Ok(Self::Raw8),
+            1 => Ok(Self::Rgb24),
+            2 => Ok(Self::Raw16),
+            3 => Ok(Self::Y8),
+            i => Err(UnknownValue { kind: "image type", value: i.into() }),
+        }
+    }
+}
+
+impl ImgType {
+    /// Number of bytes one pixel takes in the image data.
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::Raw8 | Self::Y8 => 1,
+            Self::Raw16 => 2,
+            Self::Rgb24 => 3,
         }
     }
 }
 
 /// Guider Direction
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GuideDirection {
     North,
     South,
@@ -52,7 +107,7 @@ pub enum GuideDirection {
     West,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FlipStatus {
     #[default] None,
     Horizontal,
@@ -60,19 +115,21 @@ pub enum FlipStatus {
     Both,
 }
 
-impl From<i32> for FlipStatus {
-    fn from(flip_status: i32) -> Self {
+impl TryFrom<i32> for FlipStatus {
+    type Error = UnknownValue;
+
+    fn try_from(flip_status: i32) -> Result<Self, UnknownValue> {
         match flip_status {
-            0 => FlipStatus::None,
-            1 => FlipStatus::Horizontal,
-            2 => FlipStatus::Vertical,
-            3 => FlipStatus::Both,
-            i => panic!("Invalid flip status: {}", i),
+            0 => Ok(FlipStatus::None),
+            1 => Ok(FlipStatus::Horizontal),
+            2 => Ok(FlipStatus::Vertical),
+            3 => Ok(FlipStatus::Both),
+            i => Err(UnknownValue { kind: "flip status", value: i.into() }),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CameraMode {
     Normal,
     SoftEdge,
@@ -83,22 +140,24 @@ pub enum CameraMode {
     LowLevel,
 }
 
-impl From<i32> for CameraMode {
-    fn from(camera_mode: i32) -> Self {
+impl TryFrom<i32> for CameraMode {
+    type Error = UnknownValue;
+
+    fn try_from(camera_mode: i32) -> Result<Self, UnknownValue> {
         match camera_mode {
-            0 => Self::Normal,
-            1 => Self::SoftEdge,
-            2 => Self::RiseEdge,
-            3 => Self::FallEdge,
-            4 => Self::SoftLevel,
-            5 => Self::HighLevel,
-            6 => Self::LowLevel,
-            i => panic!("Invalid camera mode: {}", i),
+            0 => Ok(Self::Normal),
+            1 => Ok(Self::SoftEdge),
+            2 => Ok(Self::RiseEdge),
+            3 => Ok(Self::FallEdge),
+            4 => Ok(Self::SoftLevel),
+            5 => Ok(Self::HighLevel),
+            6 => Ok(Self::LowLevel),
+            i => Err(UnknownValue { kind: "camera mode", value: i.into() }),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TrigOutput {
     /// Only pin A output
     PinA,
@@ -141,6 +200,16 @@ pub enum ErrorCode {
     GeneralError,
     /// The current mode is wrong.
     InvalidMode,
+    /// This camera doesn't support GPS.
+    GpsNotSupported,
+    /// The FPGA GPS version is too low.
+    GpsVersionError,
+    /// Failed to read or write data to FPGA.
+    GpsFpgaError,
+    /// Start line or end line out of range, should be between 0 and ```max_height - 1```.
+    GpsParamOutOfRange,
+    /// GPS has not yet found the satellite or FPGA cannot read GPS data.
+    GpsDataInvalid,
     Unknown,
 }
 
@@ -165,6 +234,11 @@ impl From<i32> for ErrorCode {
             15 => Self::ExposureInProgress,
             16 => Self::GeneralError,
             17 => Self::InvalidMode,
+            18 => Self::GpsNotSupported,
+            19 => Self::GpsVersionError,
+            20 => Self::GpsFpgaError,
+            21 => Self::GpsParamOutOfRange,
+            22 => Self::GpsDataInvalid,
             _ => Self::Unknown,
         }
     }
@@ -180,6 +254,193 @@ impl ErrorCode {
     }
 }
 
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::Success => "success",
+            Self::InvalidIndex => "no camera connected or index value out of boundary",
+            Self::InvalidId => "invalid ID",
+            Self::InvalidControlType => "invalid control type",
+            Self::CameraClosed => "camera didn't open",
+            Self::CameraRemoved => "failed to find the camera, maybe the camera has been removed",
+            Self::InvalidPath => "cannot find the path of the file",
+            Self::InvalidFileFormat => "invalid file format",
+            Self::InvalidSize => "wrong video format size",
+            Self::InvalidImgType => "unsupported image format",
+            Self::OutOfBoundary => "the start position is out of boundary",
+            Self::Timeout => "timeout",
+            Self::InvalidSequence => "stop capture first",
+            Self::BufferTooSmall => "buffer size is not big enough",
+            Self::VideoModeActive => "video mode is active",
+            Self::ExposureInProgress => "exposure is in progress",
+            Self::GeneralError => "general error, eg: value is out of valid range",
+            Self::InvalidMode => "the current mode is wrong",
+            Self::GpsNotSupported => "this camera doesn't support GPS",
+            Self::GpsVersionError => "the FPGA GPS version is too low",
+            Self::GpsFpgaError => "failed to read or write data to FPGA",
+            Self::GpsParamOutOfRange => "GPS start line or end line out of range",
+            Self::GpsDataInvalid => "GPS has not yet found the satellite or FPGA cannot read GPS data",
+            Self::Unknown => "unknown error",
+        };
+        f.write_str(description)
+    }
+}
+
+impl std::error::Error for ErrorCode {}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error returned by the functions of this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A function of the SDK returned an error code.
+    Sdk {
+        code: ErrorCode,
+        /// Name of the SDK function, eg. ```ASISetROIFormat```.
+        function: &'static str,
+        /// The camera the function was called for.
+        camera_id: Option<u8>,
+        /// The relevant arguments of the call, empty if there are none.
+        args: String,
+    },
+    /// The SDK returned a value which isn't known by this crate.
+    UnknownValue(UnknownValue),
+    /// The SDK version string couldn't be parsed.
+    InvalidSdkVersion(String),
+    /// The buffer can't hold one image of the current ROI, it wasn't passed to the SDK.
+    BufferTooSmall {
+        required: usize,
+        provided: usize,
+    },
+    /// The exposure didn't finish in time and was stopped.
+    ExposureTimeout {
+        timeout: Duration,
+    },
+    /// The camera reported every attempt of the exposure as failed.
+    ExposureFailed {
+        attempts: u32,
+    },
+    /// The operation was cancelled with a ```CancelToken```.
+    Cancelled,
+    /// The capture thread of a ```CaptureWorker``` isn't running anymore.
+    CaptureStopped,
+    /// The capture thread couldn't be started, with the reason from the OS.
+    SpawnFailed(String),
+    /// The camera can't capture this ROI.
+    InvalidRoi {
+        roi: Roi,
+        reason: String,
+    },
+    /// The target or tolerance of an ```AutoExposure``` can't be reached, with the reason.
+    InvalidAutoExposure(String),
+    /// The camera doesn't have this control.
+    UnsupportedControl(ControlType),
+    /// The control can only be read.
+    ControlNotWritable(ControlType),
+    /// The control can't be set to auto.
+    AutoNotSupported(ControlType),
+    /// The value is outside of ```min_value..=max_value``` of the control.
+    ControlOutOfRange {
+        control_type: ControlType,
+        value: i64,
+        min: i32,
+        max: i32,
+    },
+}
+
+/// A value returned by the SDK which doesn't map to any known variant,
+/// eg. because the camera firmware is newer than this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnknownValue {
+    /// What the value should represent, eg. ```"image type"```.
+    pub kind: &'static str,
+    pub value: i64,
+}
+
+impl fmt::Display for UnknownValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown {}: {}", self.kind, self.value)
+    }
+}
+
+impl From<UnknownValue> for Error {
+    fn from(unknown: UnknownValue) -> Self {
+        Self::UnknownValue(unknown)
+    }
+}
+
+impl Error {
+    /// The error code returned by the SDK, if the error comes from the SDK.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Sdk { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sdk { code, function, camera_id, args } => {
+                write!(f, "{} failed", function)?;
+                if let Some(camera_id) = camera_id {
+                    write!(f, " for camera {}", camera_id)?;
+                }
+                if !args.is_empty() {
+                    write!(f, " ({})", args)?;
+                }
+                write!(f, ": {}", code)
+            }
+            Self::UnknownValue(unknown) => write!(f, "SDK returned {}", unknown),
+            Self::InvalidSdkVersion(version) => write!(f, "invalid SDK version: {:?}", version),
+            Self::BufferTooSmall { required, provided } => {
+                write!(f, "buffer of {} bytes is too small, one image needs {} bytes", provided, required)
+            }
+            Self::ExposureTimeout { timeout } => write!(f, "exposure didn't finish within {:?}", timeout),
+            Self::ExposureFailed { attempts } => write!(f, "exposure failed {} times", attempts),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::CaptureStopped => write!(f, "capture thread stopped"),
+            Self::SpawnFailed(reason) => write!(f, "failed to start capture thread: {}", reason),
+            Self::InvalidRoi { roi, reason } => {
+                write!(f, "invalid ROI {}x{} at ({}, {}), bin {}, {:?}: {}", roi.width, roi.height, roi.start_x, roi.start_y, roi.bin, roi.img_type, reason)
+            }
+            Self::InvalidAutoExposure(reason) => write!(f, "invalid auto exposure: {}", reason),
+            Self::UnsupportedControl(control_type) => write!(f, "camera doesn't support the {:?} control", control_type),
+            Self::ControlNotWritable(control_type) => write!(f, "the {:?} control is read only", control_type),
+            Self::AutoNotSupported(control_type) => write!(f, "the {:?} control can't be set to auto", control_type),
+            Self::ControlOutOfRange { control_type, value, min, max } => {
+                write!(f, "value {} is out of range for the {:?} control ({}..={})", value, control_type, min, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Sdk { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes a fixed size char array filled by the SDK.
+/// The string ends at the first NUL and invalid UTF-8 is replaced.
+fn c_string(chars: &[c_char]) -> String {
+    let bytes: Vec<u8> = chars.iter().take_while(|&&c| c != 0).map(|&c| c as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Turns the status returned by the SDK ```function``` into a result.
+/// ```args``` is only evaluated if the call failed.
+fn check(status: i32, function: &'static str, camera_id: Option<u8>, args: impl FnOnce() -> String) -> Result<()> {
+    match ErrorCode::from(status) {
+        ErrorCode::Success => Ok(()),
+        code => Err(Error::Sdk { code, function, camera_id, args: args() }),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CameraInfo {
     /// The name of the camera.
@@ -206,27 +467,42 @@ pub struct CameraInfo {
     pub elec_per_adu: f32,
     pub bit_depth: u32,
     pub is_trigger_cam: bool,
+    /// Values reported by the SDK which aren't known by this crate.
+    /// Unknown video formats are left out of ```supported_video_formats``` and
+    /// an unknown bayer pattern is replaced with ```BayerPattern::Rg```.
+    pub unknown_values: Vec<UnknownValue>,
 }
 
 impl From<ASI_CAMERA_INFO> for CameraInfo {
     fn from(info: ASI_CAMERA_INFO) -> Self {
+        let mut unknown_values = Vec::new();
+
+        let bayer_pattern = BayerPattern::try_from(info.BayerPattern).unwrap_or_else(|unknown| {
+            unknown_values.push(unknown);
+            BayerPattern::Rg
+        });
+
+        let mut supported_video_formats = Vec::new();
+        for format in info.SupportedVideoFormat.iter().cloned().take_while(|&x| x != -1) {
+            match ImgType::try_from(format) {
+                Ok(format) => supported_video_formats.push(format),
+                Err(unknown) => unknown_values.push(unknown),
+            }
+        }
+
         Self {
-            name: unsafe {std::str::from_utf8_unchecked(std::mem::transmute(info.Name.as_slice())).to_string()},
+            name: c_string(&info.Name),
             camera_id: info.CameraID as u8,
             max_height: info.MaxHeight as u32,
             max_width: info.MaxWidth as u32,
             is_color_cam: info.IsColorCam == 1,
-            bayer_pattern: BayerPattern::from(info.BayerPattern),
+            bayer_pattern,
             supported_bins: {
                 let mut bins = Vec::new();
                 info.SupportedBins.iter().cloned().take_while(|&x| x != 0).for_each(|x| bins.push(x as u32));
                 bins
             },
-            supported_video_formats: {
-                let mut formats = Vec::new();
-                info.SupportedVideoFormat.iter().cloned().take_while(|&x| x != -1).for_each(|x| { formats.push(ImgType::from(x)); });
-                formats
-            },
+            supported_video_formats,
             pixel_size: info.PixelSize as f32,
             mechanical_shutter: info.MechanicalShutter == 1,
             st4_port: info.ST4Port == 1,
@@ -236,11 +512,12 @@ impl From<ASI_CAMERA_INFO> for CameraInfo {
             elec_per_adu: info.ElecPerADU,
             bit_depth: info.BitDepth as u32,
             is_trigger_cam: info.IsTriggerCam == 1,
+            unknown_values,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ControlType {
     Gain,
     Exposure,
@@ -270,7 +547,19 @@ pub enum ControlType {
     FanOn,
     PatternAdjust,
     AntiDewHeater,
-    Gps,
+    FanAdjust,
+    /// Brightness of the power LED.
+    PwrLedBright,
+    UsbHubReset,
+    GpsSupport,
+    /// Line of the frame the start GPS data is taken from.
+    GpsStartLine,
+    /// Line of the frame the end GPS data is taken from.
+    GpsEndLine,
+    /// In microseconds
+    RollingInterval,
+    /// Control type which isn't known by this crate, eg. from a newer SDK.
+    Unknown(u32),
 }
 
 impl From<u32> for ControlType {
@@ -298,8 +587,51 @@ impl From<u32> for ControlType {
             19 => ControlType::FanOn,
             20 => ControlType::PatternAdjust,
             21 => ControlType::AntiDewHeater,
-            22 => ControlType::Gps,
-            i => panic!("Invalid control type: {}", i),
+            22 => ControlType::FanAdjust,
+            23 => ControlType::PwrLedBright,
+            24 => ControlType::UsbHubReset,
+            25 => ControlType::GpsSupport,
+            26 => ControlType::GpsStartLine,
+            27 => ControlType::GpsEndLine,
+            28 => ControlType::RollingInterval,
+            i => ControlType::Unknown(i),
+        }
+    }
+}
+
+impl From<ControlType> for u32 {
+    fn from(control_type: ControlType) -> Self {
+        match control_type {
+            ControlType::Gain => 0,
+            ControlType::Exposure => 1,
+            ControlType::Gamma => 2,
+            ControlType::WbR => 3,
+            ControlType::WbB => 4,
+            ControlType::Offset => 5,
+            ControlType::BandwidthOverflow => 6,
+            ControlType::Overclock => 7,
+            ControlType::Temperature => 8,
+            ControlType::Flip => 9,
+            ControlType::AutoMaxGain => 10,
+            ControlType::AutoMaxExp => 11,
+            ControlType::AutoTargetBrightness => 12,
+            ControlType::HardwareBin => 13,
+            ControlType::HighSpeedMode => 14,
+            ControlType::CoolerPowerPerc => 15,
+            ControlType::TargetTemp => 16,
+            ControlType::CoolerOn => 17,
+            ControlType::MonoBin => 18,
+            ControlType::FanOn => 19,
+            ControlType::PatternAdjust => 20,
+            ControlType::AntiDewHeater => 21,
+            ControlType::FanAdjust => 22,
+            ControlType::PwrLedBright => 23,
+            ControlType::UsbHubReset => 24,
+            ControlType::GpsSupport => 25,
+            ControlType::GpsStartLine => 26,
+            ControlType::GpsEndLine => 27,
+            ControlType::RollingInterval => 28,
+            ControlType::Unknown(i) => i,
         }
     }
 }
@@ -321,11 +653,27 @@ pub struct ControlCaps {
     pub control_type: ControlType,
 }
 
+impl ControlCaps {
+    /// Checks that the control can be set to ```value```, returns it as the i32 the SDK takes.
+    pub fn validate(&self, value: i64, auto: bool) -> Result<i32> {
+        if !self.is_writable {
+            return Err(Error::ControlNotWritable(self.control_type));
+        }
+        if auto && !self.is_auto_supported {
+            return Err(Error::AutoNotSupported(self.control_type));
+        }
+        if value < self.min_value.into() || value > self.max_value.into() {
+            return Err(Error::ControlOutOfRange { control_type: self.control_type, value, min: self.min_value, max: self.max_value });
+        }
+        Ok(value as i32)
+    }
+}
+
 impl From<ASI_CONTROL_CAPS> for ControlCaps {
     fn from(caps: ASI_CONTROL_CAPS) -> Self {
         Self {
-            name: unsafe {std::str::from_utf8_unchecked(std::mem::transmute(caps.Name.as_slice())).to_string()},
-            description: unsafe {std::str::from_utf8_unchecked(std::mem::transmute(caps.Description.as_slice())).to_string()},
+            name: c_string(&caps.Name),
+            description: c_string(&caps.Description),
             max_value: caps.MaxValue as i32,
             min_value: caps.MinValue as i32,
             default_value: caps.DefaultValue as i32,
@@ -348,14 +696,16 @@ pub enum ExposureStatus {
     Failed,
 }
 
-impl From<u32> for ExposureStatus {
-    fn from(exposure_status: u32) -> Self {
+impl TryFrom<u32> for ExposureStatus {
+    type Error = UnknownValue;
+
+    fn try_from(exposure_status: u32) -> Result<Self, UnknownValue> {
         match exposure_status {
-            0 => Self::Idle,
-            1 => Self::Working,
-            2 => Self::Success,
-            3 => Self::Failed,
-            i => panic!("Invalid exposure status: {}", i),
+            0 => Ok(Self::Idle),
+            1 => Ok(Self::Working),
+            2 => Ok(Self::Success),
+            3 => Ok(Self::Failed),
+            i => Err(UnknownValue { kind: "exposure status", value: i.into() }),
         }
     }
 }
@@ -383,116 +733,328 @@ impl Id {
 pub struct SupportedMode {
     /// This vector will content with the support camera mode type.
     pub camera_mode: Vec<CameraMode>,
+    /// Camera modes reported by the SDK which aren't known by this crate.
+    pub unknown_values: Vec<UnknownValue>,
 }
 
 impl From<ASI_SUPPORTED_MODE> for SupportedMode {
     fn from(supported_mode: ASI_SUPPORTED_MODE) -> Self {
         let mut modes = Vec::new();
+        let mut unknown_values = Vec::new();
         for mode in supported_mode.SupportedCameraMode {
             if mode == -1 {break;}
-            modes.push(CameraMode::from(mode));
+            match CameraMode::try_from(mode) {
+                Ok(mode) => modes.push(mode),
+                Err(unknown) => unknown_values.push(unknown),
+            }
         }
 
-        Self { camera_mode: modes }
+        Self { camera_mode: modes, unknown_values }
     }
 }
 
-#[derive(Debug)]
-pub struct Camera {
+/// A connected camera that has not been opened yet.
+///
+/// This only identifies the camera, use ```open``` to get a ```Camera``` which can capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CameraRef {
     camera_id: u8
 }
 
-impl From<u8> for Camera {
-    /// Get Camera struct without opening or initializing the camera.
+impl From<u8> for CameraRef {
     fn from(id: u8) -> Self {
-        Camera { camera_id: id }
+        CameraRef { camera_id: id }
+    }
+}
+
+impl CameraRef {
+    /// The camera id used by the SDK.
+    pub fn camera_id(&self) -> u8 {
+        self.camera_id
+    }
+
+    /// Get the property of the camera, this doesn't need the camera to be opened.
+    pub fn property(&self) -> Result<CameraInfo> {
+        camera_property_by_id(self.camera_id)
+    }
+
+    /// Opens and initializes the camera.
+    pub fn open(self) -> Result<Camera> {
+        Camera::open(self.camera_id)
+    }
+}
+
+/// An opened camera.
+///
+/// The camera is closed when it's dropped. Video capture or exposure
+/// which is still running is stopped before closing.
+#[derive(Debug)]
+pub struct Camera {
+    camera_id: u8,
+    /// ```ASIOpenCamera``` succeeded and the camera wasn't closed yet.
+    opened: bool,
+    /// ```ASIInitCamera``` succeeded.
+    initialized: bool,
+    video_capture: AtomicBool,
+    exposure: AtomicBool,
+    /// Sequence number of the next frame.
+    sequence: AtomicU64,
+    /// When the running exposure was started.
+    exposure_start: Mutex<Option<Timestamp>>,
+    /// Read on the first frame, ```None``` if the camera has no serial number.
+    serial_number: OnceLock<Option<String>>,
+    /// Held while the ROI format is changed and while data is downloaded, so another thread
+    /// can't change the image size between the buffer size check and the SDK writing into the buffer.
+    format_lock: Mutex<()>,
+    /// Counts the changes of the ROI format, start position and flip, so a cached frame format can be checked.
+    layout_changes: AtomicU64,
+    /// Capabilities of all controls, loaded once when the camera is opened.
+    controls: HashMap<ControlType, ControlCaps>,
+    info: CameraInfo,
+}
+
+impl Drop for Camera {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
     }
 }
 
 impl Camera {
     /// Opens and initializes the camera.
-    pub fn open(camera_id: u8) -> Result<Self, ErrorCode> {
+    pub fn open(camera_id: u8) -> Result<Self> {
         let error = unsafe {ASIOpenCamera(camera_id.into())};
-        ErrorCode::from(error).to_result(())?;
-
+        check(error, "ASIOpenCamera", Some(camera_id), String::new)?;
+
+        let info = camera_property_by_id(camera_id).inspect_err(|_| {
+            unsafe {ASICloseCamera(camera_id.into())};
+        })?;
+
+        let mut camera = Camera {
+            camera_id,
+            opened: true,
+            initialized: false,
+            video_capture: AtomicBool::new(false),
+            exposure: AtomicBool::new(false),
+            sequence: AtomicU64::new(0),
+            exposure_start: Mutex::new(None),
+            serial_number: OnceLock::new(),
+            format_lock: Mutex::new(()),
+            layout_changes: AtomicU64::new(0),
+            controls: HashMap::new(),
+            info,
+        };
+
+        // The camera is closed again by drop if the initialization fails.
         let error = unsafe {ASIInitCamera(camera_id.into())};
-        let camera = Camera { camera_id };
-        ErrorCode::from(error).to_result(camera)
+        check(error, "ASIInitCamera", Some(camera_id), String::new)?;
+        camera.initialized = true;
+
+        for index in 0..camera.number_of_controls()? {
+            let caps = camera.control_caps(index)?;
+            camera.controls.insert(caps.control_type, caps);
+        }
+
+        Ok(camera)
     }
 
     /// Close the camera to free all the resource.
-    pub fn close(self) -> Result<(), ErrorCode> {
+    /// This is done automatically when the camera is dropped, but this way the error can be handled.
+    pub fn close(mut self) -> Result<()> {
+        self.shutdown()
+    }
+
+    /// Stops running capture and closes the camera if it's still opened.
+    fn shutdown(&mut self) -> Result<()> {
+        if !self.opened {
+            return Ok(());
+        }
+
+        if std::mem::take(self.video_capture.get_mut()) {
+            unsafe {ASIStopVideoCapture(self.camera_id.into())};
+        }
+        if std::mem::take(self.exposure.get_mut()) {
+            unsafe {ASIStopExposure(self.camera_id.into())};
+        }
+
+        self.opened = false;
+        self.initialized = false;
         let error = unsafe {ASICloseCamera(self.camera_id.into())};
-        ErrorCode::from(error).to_result(())
+        self.check(error, "ASICloseCamera")
+    }
+
+    /// The camera id used by the SDK.
+    pub fn camera_id(&self) -> u8 {
+        self.camera_id
+    }
+
+    /// The property of the camera, read when it was opened.
+    pub fn info(&self) -> &CameraInfo {
+        &self.info
+    }
+
+    /// Video capture was started and not stopped yet.
+    pub fn is_capturing_video(&self) -> bool {
+        self.video_capture.load(Ordering::SeqCst)
+    }
+
+    /// An exposure was started and its data wasn't downloaded or it wasn't stopped yet.
+    pub fn is_exposing(&self) -> bool {
+        self.exposure.load(Ordering::SeqCst)
+    }
+
+    fn next_sequence(&self) -> u64 {
+        self.sequence.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn take_exposure_start(&self) -> Option<Timestamp> {
+        self.exposure_start.lock().unwrap_or_else(PoisonError::into_inner).take()
+    }
+
+    fn cached_serial_number(&self) -> Option<String> {
+        self.serial_number.get_or_init(|| self.serial_number().ok()).clone()
+    }
+
+    fn check(&self, status: i32, function: &'static str) -> Result<()> {
+        check(status, function, Some(self.camera_id), String::new)
+    }
+
+    fn check_args(&self, status: i32, function: &'static str, args: impl FnOnce() -> String) -> Result<()> {
+        check(status, function, Some(self.camera_id), args)
     }
 
     /// Get number of controls available for this camera.
-    pub fn number_of_controls(&self) -> Result<usize, ErrorCode> {
+    pub fn number_of_controls(&self) -> Result<usize> {
         let mut num = 0;
         let error = unsafe {ASIGetNumOfControls(self.camera_id.into(), &mut num)};
-        ErrorCode::from(error).to_result(num as usize)
+        self.check(error, "ASIGetNumOfControls")?;
+        Ok(num as usize)
     }
 
     /// Get controls property available for this camera.
-    pub fn control_caps(&self, control_index: usize) -> Result<ControlCaps, ErrorCode> {
+    pub fn control_caps(&self, control_index: usize) -> Result<ControlCaps> {
         let mut control_caps = ASI_CONTROL_CAPS::default();
         let status = unsafe {ASIGetControlCaps(self.camera_id.into(), control_index as i32, &mut control_caps)};
-        ErrorCode::from(status).to_result(ControlCaps::from(control_caps))
+        self.check_args(status, "ASIGetControlCaps", || format!("index: {}", control_index))?;
+        Ok(ControlCaps::from(control_caps))
     }
 
     /// Get controls property value and auto value. Returns the value and if it is writtable or not.
     /// 
-    /// For ```ControlType::Flip``` convert it to ```FlipStatus``` with ```FlipStatus::from(value)```.
-    pub fn control_value(&self, control_type: ControlType) -> Result<(isize, bool), ErrorCode> {
+    /// For ```ControlType::Flip``` convert it to ```FlipStatus``` with ```FlipStatus::try_from(value)```.
+    pub fn control_value(&self, control_type: ControlType) -> Result<(isize, bool)> {
         let (mut value, mut auto) = (0, 0);
-        let status = unsafe {ASIGetControlValue(self.camera_id.into(), control_type as i32, &mut value, &mut auto)};
-        ErrorCode::from(status).to_result((value as isize, auto == 1))
+        let status = unsafe {ASIGetControlValue(self.camera_id.into(), u32::from(control_type) as i32, &mut value, &mut auto)};
+        self.check_args(status, "ASIGetControlValue", || format!("control: {:?}", control_type))?;
+        Ok((value as isize, auto == 1))
+    }
+
+    /// Get the capabilities of a control, ```None``` if the camera doesn't have it.
+    pub fn caps(&self, control_type: ControlType) -> Option<&ControlCaps> {
+        self.controls.get(&control_type)
+    }
+
+    /// Check if the camera has the control.
+    pub fn supports(&self, control_type: ControlType) -> bool {
+        self.controls.contains_key(&control_type)
+    }
+
+    /// Capabilities of all controls of the camera, in no particular order.
+    pub fn controls(&self) -> impl Iterator<Item = &ControlCaps> {
+        self.controls.values()
     }
 
     /// Set controls property value and auto value.
-    pub fn set_control_value(&self, control_type: ControlType, value: i32, auto: bool) -> Result<(), ErrorCode> {
-        let status = unsafe {ASISetControlValue(self.camera_id.into(), control_type as i32, value.into(), auto.into())};
-        ErrorCode::from(status).to_result(())
+    /// The value is checked against the capabilities of the control before it's sent to the camera.
+    pub fn set_control_value(&self, control_type: ControlType, value: i32, auto: bool) -> Result<()> {
+        self.set_raw_control_value(control_type, value.into(), auto)
+    }
+
+    /// Like ```set_control_value```, but values which don't fit the SDK's i32 are also out of range.
+    pub(crate) fn set_raw_control_value(&self, control_type: ControlType, value: i64, auto: bool) -> Result<()> {
+        let caps = self.caps(control_type).ok_or(Error::UnsupportedControl(control_type))?;
+        let value = caps.validate(value, auto)?;
+        if control_type == ControlType::Flip {
+            self.layout_changed();
+        }
+        let status = unsafe {ASISetControlValue(self.camera_id.into(), u32::from(control_type) as i32, value.into(), auto.into())};
+        self.check_args(status, "ASISetControlValue", || format!("control: {:?}, value: {}, auto: {}", control_type, value, auto))
     }
 
     /// Get the current ROI area setting.
-    pub fn roi_format(&self) -> Result<(u32, u32, i32, ImgType), ErrorCode> {
+    pub fn roi_format(&self) -> Result<(u32, u32, i32, ImgType)> {
         let (mut width, mut height, mut bin, mut img_type) = (0, 0, 0, ASI_IMG_TYPE::default());
         let status = unsafe {ASIGetROIFormat(self.camera_id.into(), &mut width, &mut height, &mut bin, &mut img_type)};
-        ErrorCode::from(status).to_result((width as u32, height as u32, bin, ImgType::from(img_type)))
+        self.check(status, "ASIGetROIFormat")?;
+        Ok((width as u32, height as u32, bin, ImgType::try_from(img_type)?))
     }
 
     /// Set the ROI area before capture.
     /// You must stop the capture before call it.
     /// The width and height is the value after binning.
-    pub fn set_roi_format(&self, width: u32, height: u32, bin: u32, img_type: ImgType) -> Result<(), ErrorCode> {
+    /// A download running on another thread is finished first.
+    pub fn set_roi_format(&self, width: u32, height: u32, bin: u32, img_type: ImgType) -> Result<()> {
+        let _format = self.lock_format();
+        self.layout_changed();
         let status = unsafe {ASISetROIFormat(self.camera_id.into(), width as i32, height as i32, bin as i32, img_type as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASISetROIFormat", || format!("width: {}, height: {}, bin: {}, img_type: {:?}", width, height, bin, img_type))
+    }
+
+    /// Size in bytes of one image of the current ROI.
+    pub fn required_buffer_size(&self) -> Result<usize> {
+        let (width, height, _, img_type) = self.roi_format()?;
+        Ok(width as usize * height as usize * img_type.bytes_per_pixel())
+    }
+
+    /// Keeps the ROI format from changing until the guard is dropped.
+    pub(crate) fn lock_format(&self) -> MutexGuard<'_, ()> {
+        self.format_lock.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Changes with every call which may change the format of the frames.
+    /// With the format locked, the size of the frames is the same as long as this is.
+    pub(crate) fn layout_changes(&self) -> u64 {
+        self.layout_changes.load(Ordering::SeqCst)
+    }
+
+    fn layout_changed(&self) {
+        self.layout_changes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Has to be called with the format locked, until the download is done.
+    fn check_buffer_size(&self, buffer: &[u8]) -> Result<()> {
+        let required = self.required_buffer_size()?;
+        if buffer.len() < required {
+            return Err(Error::BufferTooSmall { required, provided: buffer.len() });
+        }
+        Ok(())
     }
 
     /// Get the start position of current ROI area.
-    pub fn start_position(&self) -> Result<(u32, u32), ErrorCode> {
+    pub fn start_position(&self) -> Result<(u32, u32)> {
         let (mut start_x, mut start_y) = (0, 0);
         let status = unsafe {ASIGetStartPos(self.camera_id.into(), &mut start_x, &mut start_y)};
-        ErrorCode::from(status).to_result((start_x as u32, start_y as u32))
+        self.check(status, "ASIGetStartPos")?;
+        Ok((start_x as u32, start_y as u32))
     }
 
     /// Set the start position of the ROI area.
     /// You can call this API to move the ROI area when video is streaming.
     /// The camera will set the ROI area to the center of the full image as default.
     /// At bin2 or bin3 mode, the position is relative to the image after binning.
-    pub fn set_start_position(&self, start_x: u32, start_y: u32) -> Result<(), ErrorCode> {
+    pub fn set_start_position(&self, start_x: u32, start_y: u32) -> Result<()> {
+        self.layout_changed();
         let status = unsafe {ASISetStartPos(self.camera_id.into(), start_x as i32, start_y as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASISetStartPos", || format!("x: {}, y: {}", start_x, start_y))
     }
 
     /// Get the dropped frames.
     /// Dropped frames happen when USB traffic or harddisk write speed is slow.
     /// It will reset to 0 after stop capture.
-    pub fn get_dropped_frames(&self) -> Result<u32, ErrorCode> {
+    pub fn get_dropped_frames(&self) -> Result<u32> {
         let mut dropped_frames = 0;
         let status = unsafe {ASIGetDroppedFrames(self.camera_id.into(), &mut dropped_frames)};
-        ErrorCode::from(status).to_result(dropped_frames as u32)
+        self.check(status, "ASIGetDroppedFrames")?;
+        Ok(dropped_frames as u32)
     }
 
     /// Provide a dark file's path to the function and enable dark subtract.
@@ -503,162 +1065,199 @@ impl Camera {
     /// and should be RGB8 raw format. It will be on even if you change the ROI setting.
     /// It only corrects hot pixels if output isn't 16bit.
     /// It will be remembered in registry, so "Dark subtract" is on next time if you close your app.
-    pub fn enable_dark_subtract(&self, path: &str) -> Result<(), ErrorCode> {
-        let path = CString::new(path).unwrap().into_raw();
-        let status = unsafe {ASIEnableDarkSubtract(self.camera_id.into(), path)};
-        ErrorCode::from(status).to_result(())
+    pub fn enable_dark_subtract(&self, path: &str) -> Result<()> {
+        let c_path = CString::new(path).unwrap();
+        let status = unsafe {ASIEnableDarkSubtract(self.camera_id.into(), c_path.as_ptr() as *mut _)};
+        self.check_args(status, "ASIEnableDarkSubtract", || format!("path: {:?}", path))
     }
 
     /// Disable the dark subtract function.
     /// You'd better call it at start if you don't want to use it,
     /// because dark subtract function is remembered on windows platform.
-    pub fn disable_dark_subtract(&self) -> Result<(), ErrorCode> {
+    pub fn disable_dark_subtract(&self) -> Result<()> {
         let status = unsafe {ASIDisableDarkSubtract(self.camera_id.into())};
-        ErrorCode::from(status).to_result(())
+        self.check(status, "ASIDisableDarkSubtract")
     }
 
     /// Start video capture.
     /// Then you can get the data from function get_video_data.
-    pub fn start_video_capture(&self) -> Result<(), ErrorCode> {
+    pub fn start_video_capture(&self) -> Result<()> {
         let status = unsafe {ASIStartVideoCapture(self.camera_id.into())};
-        ErrorCode::from(status).to_result(())
+        self.check(status, "ASIStartVideoCapture")?;
+        self.video_capture.store(true, Ordering::SeqCst);
+        Ok(())
     }
 
     /// Stop video capture.
-    pub fn stop_video_capture(&self) -> Result<(), ErrorCode> {
+    pub fn stop_video_capture(&self) -> Result<()> {
         let status = unsafe {ASIStopVideoCapture(self.camera_id.into())};
-        ErrorCode::from(status).to_result(())
+        self.check(status, "ASIStopVideoCapture")?;
+        self.video_capture.store(false, Ordering::SeqCst);
+        Ok(())
     }
 
     /// Get data from the video buffer. The buffer is very small.
     /// You need to call this API as fast as possible, otherwise frame will be discarded.
     /// The best way is maintain one buffer loop and call this API in a loop.
-    /// Please make sure the buffer size is big enough to hold one image
-    /// otherwise the this API will crash.
-    pub fn get_video_data(&self, buffer: &mut [u8], wait_ms: u32) -> Result<(), ErrorCode> {
+    /// The buffer has to hold one image of the current ROI, otherwise ```Error::BufferTooSmall``` is returned.
+    pub fn get_video_data(&self, buffer: &mut [u8], wait_ms: u32) -> Result<()> {
+        let _format = self.lock_format();
+        self.check_buffer_size(buffer)?;
+        self.get_video_data_unchecked(buffer, wait_ms)
+    }
+
+    /// The SDK crashes if the buffer can't hold one image of the current ROI, the format has to be locked.
+    pub(crate) fn get_video_data_unchecked(&self, buffer: &mut [u8], wait_ms: u32) -> Result<()> {
         let status = unsafe {ASIGetVideoData(self.camera_id.into(), buffer.as_mut_ptr(), (buffer.len() as i32).into(), wait_ms as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASIGetVideoData", || format!("buffer size: {}, wait: {}ms", buffer.len(), wait_ms))
     }
 
     /// PulseGuide of the ST4 port on. This function only works on modules which have ST4 port.
-    pub fn pulse_guide_on(&self, direction: GuideDirection) -> Result<(), ErrorCode> {
+    pub fn pulse_guide_on(&self, direction: GuideDirection) -> Result<()> {
         let status = unsafe {ASIPulseGuideOn(self.camera_id.into(), direction as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASIPulseGuideOn", || format!("direction: {:?}", direction))
     }
 
     /// PulseGuide of the ST4 port off. This function only works on modules which have ST4 port.
-    pub fn pulse_guide_off(&self, direction: GuideDirection) -> Result<(), ErrorCode> {
+    pub fn pulse_guide_off(&self, direction: GuideDirection) -> Result<()> {
         let status = unsafe {ASIPulseGuideOff(self.camera_id.into(), direction as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASIPulseGuideOff", || format!("direction: {:?}", direction))
     }
 
     /// Start camera exposure.
     /// Start exposure and check the exposure status then get the data.
     /// ```is_dark``` means dark frame if there is mechanical shutter on the camera otherwise useless.
-    pub fn start_exposure(&self, is_dark: bool) -> Result<(), ErrorCode> {
+    pub fn start_exposure(&self, is_dark: bool) -> Result<()> {
+        let start = Timestamp::now();
         let status = unsafe {ASIStartExposure(self.camera_id.into(), is_dark as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASIStartExposure", || format!("is_dark: {}", is_dark))?;
+        *self.exposure_start.lock().unwrap_or_else(PoisonError::into_inner) = Some(start);
+        self.exposure.store(true, Ordering::SeqCst);
+        Ok(())
     }
 
     /// To cancel the long exposure which is on.
-    pub fn stop_exposure(&self) -> Result<(), ErrorCode> {
+    pub fn stop_exposure(&self) -> Result<()> {
         let status = unsafe {ASIStopExposure(self.camera_id.into())};
-        ErrorCode::from(status).to_result(())
+        self.check(status, "ASIStopExposure")?;
+        self.exposure.store(false, Ordering::SeqCst);
+        Ok(())
     }
 
     /// To get the exposure status, work with start_exposure.
     /// You can read the data if you get ```ExposureStatus::Success``` or you have to restart exposure again
     /// if you get ```ExposureStatus::Failed```
-    pub fn exposure_status(&self) -> Result<ExposureStatus, ErrorCode> {
+    pub fn exposure_status(&self) -> Result<ExposureStatus> {
         let mut exposure_status = ASI_EXPOSURE_STATUS::default();
         let status = unsafe {ASIGetExpStatus(self.camera_id.into(), &mut exposure_status)};
-        ErrorCode::from(status).to_result(ExposureStatus::from(exposure_status))
+        self.check(status, "ASIGetExpStatus")?;
+        Ok(ExposureStatus::try_from(exposure_status)?)
     }
 
     /// Get data after exposure.
-    /// Please make sure the buffer size is big enough to hold one image
-    /// otherwise the this API will crash.
-    pub fn get_data_after_exposure(&self, buffer: &mut [u8]) -> Result<(), ErrorCode> {
+    /// The buffer has to hold one image of the current ROI, otherwise ```Error::BufferTooSmall``` is returned.
+    pub fn get_data_after_exposure(&self, buffer: &mut [u8]) -> Result<()> {
+        let _format = self.lock_format();
+        self.check_buffer_size(buffer)?;
+        self.get_data_after_exposure_unchecked(buffer)
+    }
+
+    /// The SDK crashes if the buffer can't hold one image of the current ROI, the format has to be locked.
+    pub(crate) fn get_data_after_exposure_unchecked(&self, buffer: &mut [u8]) -> Result<()> {
         let status = unsafe {ASIGetDataAfterExp(self.camera_id.into(), buffer.as_mut_ptr(), (buffer.len() as i32).into())};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASIGetDataAfterExp", || format!("buffer size: {}", buffer.len()))?;
+        self.exposure_finished();
+        Ok(())
+    }
+
+    /// The data of the exposure was downloaded.
+    fn exposure_finished(&self) {
+        self.exposure.store(false, Ordering::SeqCst);
     }
 
     /// Get camera id stored in flash, only available for USB3.0 cameras.
-    pub fn id(&self) -> Result<Id, ErrorCode> {
+    pub fn id(&self) -> Result<Id> {
         let mut id = ASI_ID::default();
         let status = unsafe {ASIGetID(self.camera_id.into(), &mut id)};
-        ErrorCode::from(status).to_result(Id::from(id))
+        self.check(status, "ASIGetID")?;
+        Ok(Id::from(id))
     }
 
     /// Write camera id to flash, only available for USB3.0 cameras.
-    pub fn set_id(&self, id: Id) -> Result<(), ErrorCode> {
+    pub fn set_id(&self, id: Id) -> Result<()> {
         let status = unsafe {ASISetID(self.camera_id.into(), id.to_asi_id())};
-        ErrorCode::from(status).to_result(())
+        self.check(status, "ASISetID")
     }
 
     /// Get pre-setting parameter.
-    pub fn gain_offset(&self) -> Result<(u32, u32, u32, u32), ErrorCode> {
+    pub fn gain_offset(&self) -> Result<(u32, u32, u32, u32)> {
         let (mut off_hig_dr, mut off_unity_gain, mut gain_low_rn, mut off_low_rn) = (0, 0, 0, 0);
         let status = unsafe {ASIGetGainOffset(self.camera_id.into(), &mut off_hig_dr, &mut off_unity_gain, &mut gain_low_rn, &mut off_low_rn)};
-        ErrorCode::from(status).to_result((off_hig_dr as u32, off_unity_gain as u32, gain_low_rn as u32, off_low_rn as u32))
+        self.check(status, "ASIGetGainOffset")?;
+        Ok((off_hig_dr as u32, off_unity_gain as u32, gain_low_rn as u32, off_low_rn as u32))
     }
 
     /// Get the frequently-used gain and offset.
-    pub fn lmh_gain_offset(&self) -> Result<(u32, u32, u32, u32), ErrorCode> {
+    pub fn lmh_gain_offset(&self) -> Result<(u32, u32, u32, u32)> {
         let (mut l_gain, mut m_gain, mut h_gain, mut h_offset) = (0, 0, 0, 0);
         let status = unsafe {ASIGetLMHGainOffset(self.camera_id.into(), &mut l_gain, &mut m_gain, &mut h_gain, &mut h_offset)};
-        ErrorCode::from(status).to_result((l_gain as u32, m_gain as u32, h_gain as u32, h_offset as u32))
+        self.check(status, "ASIGetLMHGainOffset")?;
+        Ok((l_gain as u32, m_gain as u32, h_gain as u32, h_offset as u32))
     }
 
     /// Get the camera supported mode, only needs to call when the ```is_trigger_cam``` in the ```CameraInfo``` is ```true```.
-    pub fn camera_supported_mode(&self) -> Result<SupportedMode, ErrorCode> {
+    pub fn camera_supported_mode(&self) -> Result<SupportedMode> {
         let mut supported_mode = ASI_SUPPORTED_MODE::default();
         let status = unsafe {ASIGetCameraSupportMode(self.camera_id.into(), &mut supported_mode)};
-        ErrorCode::from(status).to_result(SupportedMode::from(supported_mode))
+        self.check(status, "ASIGetCameraSupportMode")?;
+        Ok(SupportedMode::from(supported_mode))
     }
 
     /// Get the camera current mode, only needs to call when the ```is_trigger_cam``` in the ```CameraInfo``` is ```true```.
-    pub fn camera_mode(&self) -> Result<CameraMode, ErrorCode> {
+    pub fn camera_mode(&self) -> Result<CameraMode> {
         let mut camera_mode = ASI_CAMERA_MODE::default();
         let status = unsafe {ASIGetCameraMode(self.camera_id.into(), &mut camera_mode)};
-        ErrorCode::from(status).to_result(CameraMode::from(camera_mode))
+        self.check(status, "ASIGetCameraMode")?;
+        Ok(CameraMode::try_from(camera_mode)?)
     }
 
     /// Set the camera mode, only needs to call when the ```is_trigger_cam``` in the ```CameraInfo``` is ```true```.
-    pub fn set_camera_mode(&self, camera_mode: CameraMode) -> Result<(), ErrorCode> {
+    pub fn set_camera_mode(&self, camera_mode: CameraMode) -> Result<()> {
         let status = unsafe {ASISetCameraMode(self.camera_id.into(), camera_mode as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASISetCameraMode", || format!("mode: {:?}", camera_mode))
     }
 
     /// Send out a softTrigger. For edge trigger, it only needs to set true which means send a
     /// rising trigger to start exposure. For level trigger, it needs to set true first means 
     /// start exposure, and set false means stop exposure. It only needs to call when the 
     /// ```is_trigger_cam``` in the ```CameraInfo``` is ```true```.
-    pub fn send_soft_trigger(&self, start: bool) -> Result<(), ErrorCode> {
+    pub fn send_soft_trigger(&self, start: bool) -> Result<()> {
         let status = unsafe {ASISendSoftTrigger(self.camera_id.into(), start as i32)};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASISendSoftTrigger", || format!("start: {}", start))
     }
 
     /// Get a serial number from the camera.
-    pub fn serial_number(&self) -> Result<String, ErrorCode> {
+    pub fn serial_number(&self) -> Result<String> {
         let mut sn = ASI_SN::default();
         let status = unsafe {ASIGetSerialNumber(self.camera_id.into(), &mut sn)};
         let serial_number = hex::encode(sn.id);
-        ErrorCode::from(status).to_result(serial_number)
+        self.check(status, "ASIGetSerialNumber")?;
+        Ok(serial_number)
     }
 
     /// Get the output pin configuration, it only needs to call when the is_trigger_cam in the CameraInfo is true.
-    pub fn trigger_output_io_conf(&self, pin: TrigOutput) -> Result<(bool, usize, usize), ErrorCode> {
+    pub fn trigger_output_io_conf(&self, pin: TrigOutput) -> Result<(bool, usize, usize)> {
         let (mut pin_high, mut delay, mut duration) = (0, 0, 0);
         let status = unsafe {ASIGetTriggerOutputIOConf(self.camera_id.into(), pin as i32, &mut pin_high, &mut delay, &mut duration)};
-        ErrorCode::from(status).to_result((pin_high == 1, delay as usize, duration as usize))
+        self.check_args(status, "ASIGetTriggerOutputIOConf", || format!("pin: {:?}", pin))?;
+        Ok((pin_high == 1, delay as usize, duration as usize))
     }
 
     /// Config the output pin (A or B) of Trigger port. If duration <= 0, this output pin will be closed. 
     /// It only needs to call when the is_trigger_cam in the CameraInfo is true.
-    pub fn set_trigger_output_io_conf(&self, pin: TrigOutput, pin_high: bool, delay: usize, duration: usize) -> Result<(), ErrorCode> {
+    pub fn set_trigger_output_io_conf(&self, pin: TrigOutput, pin_high: bool, delay: usize, duration: usize) -> Result<()> {
         let status = unsafe {ASISetTriggerOutputIOConf(self.camera_id.into(), pin as i32, pin_high as i32, (delay as i32).into(), (duration as i32).into())};
-        ErrorCode::from(status).to_result(())
+        self.check_args(status, "ASISetTriggerOutputIOConf", || format!("pin: {:?}, pin_high: {}, delay: {}, duration: {}", pin, pin_high, delay, duration))
     }
 }
 
@@ -670,8 +1269,12 @@ pub fn number_of_connected_cameras() -> u8 {
 
 /// Get the product ID of each supported camera.
 pub fn product_ids() -> Vec<i32> {
-    let mut pids = Vec::new();
-    unsafe {ASIGetProductIDs(pids.as_mut_ptr())};
+    let len = unsafe {ASIGetProductIDs(std::ptr::null_mut())};
+    let mut pids = vec![0; len.max(0) as usize];
+    if !pids.is_empty() {
+        let len = unsafe {ASIGetProductIDs(pids.as_mut_ptr())};
+        pids.truncate(len.max(0) as usize);
+    }
     pids
 }
 
@@ -681,20 +1284,78 @@ pub fn camera_check(vid: i32, pid: i32) -> bool {
 }
 
 /// Get the property of connected cameras, you can do this without open the camera.
-pub fn camera_property(camera_index: u8) -> Result<CameraInfo, ErrorCode> {
+pub fn camera_property(camera_index: u8) -> Result<CameraInfo> {
     let mut camera_info = ASI_CAMERA_INFO::default();
     let error = unsafe {ASIGetCameraProperty(&mut camera_info, camera_index as i32)};
-    ErrorCode::from(error).to_result(CameraInfo::from(camera_info))
+    check(error, "ASIGetCameraProperty", None, || format!("index: {}", camera_index))?;
+    Ok(CameraInfo::from(camera_info))
 }
 
 /// Get the property of the connected cameras by ID.
-pub fn camera_property_by_id(camera_id: u8) -> Result<CameraInfo, ErrorCode> {
+pub fn camera_property_by_id(camera_id: u8) -> Result<CameraInfo> {
     let mut camera_info = ASI_CAMERA_INFO::default();
     let error = unsafe {ASIGetCameraPropertyByID(camera_id.into(), &mut camera_info)};
-    ErrorCode::from(error).to_result(CameraInfo::from(camera_info))
+    check(error, "ASIGetCameraPropertyByID", Some(camera_id), String::new)?;
+    Ok(CameraInfo::from(camera_info))
+}
+
+/// Get the property of all connected cameras.
+pub fn connected_cameras() -> Result<Vec<CameraInfo>> {
+    (0..number_of_connected_cameras()).map(camera_property).collect()
+}
+
+/// Find a connected camera by its name, eg. ```"ZWO ASI294MC Pro"```.
+pub fn find_camera(name: &str) -> Result<Option<CameraInfo>> {
+    Ok(connected_cameras()?.into_iter().find(|info| info.name == name))
 }
 
 /// Get version string, like "1, 13, 0503", for ASI SDK
-pub fn sdk_version() -> String {
-    unsafe {CString::from_raw(ASIGetSDKVersion()).to_string_lossy().to_string()}
+pub fn sdk_version_string() -> String {
+    let version = unsafe {ASIGetSDKVersion()};
+    if version.is_null() {
+        return String::new();
+    }
+    // The string is owned by the SDK, so it's only borrowed here.
+    unsafe {CStr::from_ptr(version)}.to_string_lossy().into_owned()
+}
+
+/// Get the version of the ASI SDK.
+pub fn sdk_version() -> Result<SdkVersion> {
+    sdk_version_string().parse()
+}
+
+/// Version of the ASI SDK, ordered so it can be compared against a minimum required version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SdkVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub build: u32,
+}
+
+impl SdkVersion {
+    pub const fn new(major: u32, minor: u32, build: u32) -> Self {
+        Self { major, minor, build }
+    }
+}
+
+impl FromStr for SdkVersion {
+    type Err = Error;
+
+    /// Parses the format returned by the SDK, like "1, 13, 0503".
+    fn from_str(version: &str) -> Result<Self> {
+        let invalid = || Error::InvalidSdkVersion(version.to_string());
+        let mut parts = version.split(',').map(|part| part.trim().parse::<u32>().map_err(|_| invalid()));
+        let mut next = || parts.next().unwrap_or_else(|| Err(invalid()));
+        let version = SdkVersion { major: next()?, minor: next()?, build: next()? };
+        match parts.next() {
+            None => Ok(version),
+            Some(_) => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for SdkVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{:04}", self.major, self.minor, self.build)
+    }
 }
@@ -0,0 +1,167 @@
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{Camera, Celsius, ControlType, GpsData, Roi};
+
+/// A point in time taken on the host from the monotonic and the wall clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    /// Use this one for intervals, it isn't affected by clock adjustments.
+    pub monotonic: Instant,
+    pub utc: SystemTime,
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Self { monotonic: Instant::now(), utc: SystemTime::now() }
+    }
+
+    /// Move both clocks back, the monotonic clock saturates at its earliest possible value.
+    pub fn before(&self, duration: Duration) -> Self {
+        Self {
+            monotonic: self.monotonic.checked_sub(duration).unwrap_or(self.monotonic),
+            utc: self.utc - duration,
+        }
+    }
+
+    pub fn after(&self, duration: Duration) -> Self {
+        Self { monotonic: self.monotonic + duration, utc: self.utc + duration }
+    }
+
+    /// The UTC time in ISO 8601 format with microseconds, eg. ```2024-03-01T21:04:13.250000```,
+    /// like the ```DATE-OBS``` keyword of FITS.
+    pub fn utc_string(&self) -> String {
+        let since_epoch = self.utc.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = since_epoch.as_secs();
+        let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+        let seconds_of_day = seconds % 86400;
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}",
+            year,
+            month,
+            day,
+            seconds_of_day / 3600,
+            seconds_of_day / 60 % 60,
+            seconds_of_day % 60,
+            since_epoch.subsec_micros(),
+        )
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.utc_string())
+    }
+}
+
+/// Converts days since 1970-01-01 into year, month and day of the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// What was known about a frame when it was captured.
+///
+/// The timestamps are taken on the host, so they include the USB latency.
+/// Camera values are ```None``` if the camera doesn't have the control or reading it failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameMetadata {
+    /// Counts the frames of a camera since it was opened, starting with 0.
+    pub sequence: u64,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub exposure: Option<Duration>,
+    pub gain: Option<i32>,
+    pub offset: Option<i32>,
+    pub temperature: Option<Celsius>,
+    /// Target temperature of the cooler.
+    pub target_temperature: Option<Celsius>,
+    /// Format and start position of the frame.
+    pub roi: Roi,
+    pub serial_number: Option<String>,
+    pub camera_name: String,
+    /// Only set for frames captured with GPS data.
+    pub gps: Option<GpsData>,
+}
+
+impl FrameMetadata {
+    /// Middle of the exposure, eg. for photometry.
+    pub fn mid_exposure(&self) -> Timestamp {
+        let duration = self.end.monotonic.saturating_duration_since(self.start.monotonic);
+        self.start.after(duration / 2)
+    }
+}
+
+/// How often cached controls in the frame metadata are read again, eg. for the temperature.
+pub(crate) const CONTROLS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Control values for the metadata of a frame, ```None``` if the camera doesn't have the control or reading it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct FrameControls {
+    pub exposure: Option<Duration>,
+    pub gain: Option<i32>,
+    pub offset: Option<i32>,
+    pub temperature: Option<Celsius>,
+    pub target_temperature: Option<Celsius>,
+}
+
+impl Camera {
+    /// Read the controls which go into the metadata of a frame.
+    pub(crate) fn frame_controls(&self) -> FrameControls {
+        let read = |control_type| match self.supports(control_type) {
+            true => self.control_value(control_type).ok().map(|(value, _)| value),
+            false => None,
+        };
+
+        FrameControls {
+            exposure: read(ControlType::Exposure).map(|us| Duration::from_micros(us.max(0) as u64)),
+            gain: read(ControlType::Gain).map(|gain| gain as i32),
+            offset: read(ControlType::Offset).map(|offset| offset as i32),
+            temperature: read(ControlType::Temperature).map(|temperature| Celsius::from_tenths(temperature as i64)),
+            target_temperature: read(ControlType::TargetTemp).map(|temperature| Celsius(temperature as f64)),
+        }
+    }
+
+    /// Metadata of a frame which was exposed from ```start``` to ```end``` with this ROI and these controls.
+    pub(crate) fn frame_metadata(&self, start: Timestamp, end: Timestamp, roi: Roi, controls: FrameControls) -> FrameMetadata {
+        FrameMetadata {
+            sequence: self.next_sequence(),
+            start,
+            end,
+            exposure: controls.exposure,
+            gain: controls.gain,
+            offset: controls.offset,
+            temperature: controls.temperature,
+            target_temperature: controls.target_temperature,
+            roi,
+            serial_number: self.cached_serial_number(),
+            camera_name: self.info().name.clone(),
+            gps: None,
+        }
+    }
+
+    /// Timestamps of an exposure which was downloaded at ```now```.
+    /// The end is the start plus the exposure time, but never later than now.
+    pub(crate) fn exposure_timestamps(&self, now: Timestamp, exposure: Option<Duration>) -> (Timestamp, Timestamp) {
+        let exposure = exposure.unwrap_or_default();
+        let start = self.take_exposure_start().unwrap_or_else(|| now.before(exposure));
+        let end = match start.monotonic + exposure < now.monotonic {
+            true => start.after(exposure),
+            false => now,
+        };
+        (start, end)
+    }
+}
+
+/// Timestamps of a video frame which arrived at ```end```, it's assumed to end then.
+pub(crate) fn video_timestamps(end: Timestamp, exposure: Option<Duration>) -> (Timestamp, Timestamp) {
+    (end.before(exposure.unwrap_or_default()), end)
+}
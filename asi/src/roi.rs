@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Camera, CameraInfo, Error, ImgType, Result};
+
+/// Region of interest: the output format together with its start position.
+///
+/// Width, height and start position are in binned pixels. Build it with
+/// ```Roi::full``` or ```Roi::centered``` so it's checked against the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Roi {
+    pub width: u32,
+    pub height: u32,
+    pub bin: u32,
+    pub img_type: ImgType,
+    pub start_x: u32,
+    pub start_y: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RoiSize {
+    Full,
+    Sized(u32, u32),
+}
+
+/// Builder for a ```Roi```, the geometry is only resolved against a ```CameraInfo```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RoiBuilder {
+    size: RoiSize,
+    position: Option<(u32, u32)>,
+    bin: u32,
+    img_type: ImgType,
+}
+
+impl Roi {
+    /// The whole sensor.
+    pub fn full() -> RoiBuilder {
+        RoiBuilder { size: RoiSize::Full, position: None, bin: 1, img_type: ImgType::Raw8 }
+    }
+
+    /// ROI of the given size after binning in the center of the sensor.
+    pub fn centered(width: u32, height: u32) -> RoiBuilder {
+        RoiBuilder { size: RoiSize::Sized(width, height), position: None, bin: 1, img_type: ImgType::Raw8 }
+    }
+
+    /// Check that the camera can capture this ROI.
+    ///
+    /// The width must be a multiple of 8, the height a multiple of 2, bin and format
+    /// must be supported and the ROI has to be inside of the binned sensor.
+    pub fn validate(&self, info: &CameraInfo) -> Result<()> {
+        let invalid = |reason: String| Err(Error::InvalidRoi { roi: *self, reason });
+
+        if !info.supported_bins.contains(&self.bin) {
+            return invalid(format!("bin {} isn't supported, supported bins are {:?}", self.bin, info.supported_bins));
+        }
+        if !info.supported_video_formats.contains(&self.img_type) {
+            return invalid(format!("{:?} isn't supported, supported formats are {:?}", self.img_type, info.supported_video_formats));
+        }
+        if self.width == 0 || !self.width.is_multiple_of(8) {
+            return invalid(format!("width {} isn't a multiple of 8", self.width));
+        }
+        if self.height == 0 || !self.height.is_multiple_of(2) {
+            return invalid(format!("height {} isn't a multiple of 2", self.height));
+        }
+        if needs_1024_multiple(info) && !is_1024_multiple(self.width, self.height) {
+            return invalid(format!("{}x{} isn't a multiple of 1024 pixels", self.width, self.height));
+        }
+
+        let (max_width, max_height) = binned_sensor(info, self.bin);
+        let outside = |start: u32, size: u32, max: u32| start.checked_add(size).is_none_or(|end| end > max);
+        if outside(self.start_x, self.width, max_width) || outside(self.start_y, self.height, max_height) {
+            return invalid(format!("outside of the {}x{} sensor at bin {}", max_width, max_height, self.bin));
+        }
+
+        Ok(())
+    }
+}
+
+impl RoiBuilder {
+    /// Place the ROI at this start position instead of the center.
+    pub fn at(mut self, start_x: u32, start_y: u32) -> Self {
+        self.position = Some((start_x, start_y));
+        self
+    }
+
+    pub fn bin(mut self, bin: u32) -> Self {
+        self.bin = bin;
+        self
+    }
+
+    pub fn format(mut self, img_type: ImgType) -> Self {
+        self.img_type = img_type;
+        self
+    }
+
+    /// Resolve the ROI for the camera and check that it's valid.
+    pub fn build(&self, info: &CameraInfo) -> Result<Roi> {
+        let (max_width, max_height) = binned_sensor(info, self.bin);
+        let (width, height) = match self.size {
+            RoiSize::Full => (max_width / 8 * 8, max_height / 2 * 2),
+            RoiSize::Sized(width, height) => (width, height),
+        };
+        let roi = self.place(width, height, max_width, max_height);
+        roi.validate(info)?;
+        Ok(roi)
+    }
+
+    /// Resolve the ROI for the camera, moving the size and position to the nearest valid geometry.
+    /// Only an unsupported bin or format is an error.
+    pub fn snap(&self, info: &CameraInfo) -> Result<Roi> {
+        let (max_width, max_height) = binned_sensor(info, self.bin);
+        let (width, height) = match self.size {
+            RoiSize::Full => (max_width, max_height),
+            RoiSize::Sized(width, height) => (width, height),
+        };
+
+        let width = nearest_multiple(width, 8, max_width);
+        let mut height = nearest_multiple(height, 2, max_height);
+        if needs_1024_multiple(info) {
+            while height > 2 && !is_1024_multiple(width, height) {
+                height -= 2;
+            }
+        }
+
+        let mut roi = self.place(width, height, max_width, max_height);
+        roi.start_x = roi.start_x.min(max_width.saturating_sub(width));
+        roi.start_y = roi.start_y.min(max_height.saturating_sub(height));
+        roi.validate(info)?;
+        Ok(roi)
+    }
+
+    fn place(&self, width: u32, height: u32, max_width: u32, max_height: u32) -> Roi {
+        let (start_x, start_y) = self.position.unwrap_or((
+            max_width.saturating_sub(width) / 2,
+            max_height.saturating_sub(height) / 2,
+        ));
+        Roi { width, height, bin: self.bin, img_type: self.img_type, start_x, start_y }
+    }
+}
+
+/// Size of the sensor after binning.
+fn binned_sensor(info: &CameraInfo, bin: u32) -> (u32, u32) {
+    let bin = bin.max(1);
+    (info.max_width / bin, info.max_height / bin)
+}
+
+/// The USB2 ASI120 needs the number of pixels to be a multiple of 1024.
+fn needs_1024_multiple(info: &CameraInfo) -> bool {
+    info.name.contains("ASI120") && !info.is_usb3_camera
+}
+
+fn is_1024_multiple(width: u32, height: u32) -> bool {
+    width.checked_mul(height).is_some_and(|pixels| pixels.is_multiple_of(1024))
+}
+
+/// Round to the nearest multiple of ```step``` which is in ```step..=max```.
+fn nearest_multiple(value: u32, step: u32, max: u32) -> u32 {
+    let max = (max / step * step).max(step);
+    (value.saturating_add(step / 2) / step * step).clamp(step, max)
+}
+
+impl Camera {
+    /// Get the current ROI together with its start position.
+    pub fn roi(&self) -> Result<Roi> {
+        let (width, height, bin, img_type) = self.roi_format()?;
+        let (start_x, start_y) = self.start_position()?;
+        Ok(Roi { width, height, bin: bin as u32, img_type, start_x, start_y })
+    }
+
+    /// Set the format and the start position of the ROI.
+    ///
+    /// The ROI is validated first. If the start position can't be set,
+    /// the previous ROI is restored. You must stop the capture before calling it.
+    pub fn set_roi(&self, roi: &Roi) -> Result<()> {
+        roi.validate(self.info())?;
+
+        let previous = self.roi()?;
+        self.set_roi_format(roi.width, roi.height, roi.bin, roi.img_type)?;
+        if let Err(error) = self.set_start_position(roi.start_x, roi.start_y) {
+            let _ = self.set_roi_format(previous.width, previous.height, previous.bin, previous.img_type);
+            let _ = self.set_start_position(previous.start_x, previous.start_y);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
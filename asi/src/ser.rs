@@ -0,0 +1,403 @@
+//! Recording and replaying video in the SER format, version 3.
+//!
+//! SER files are read by stacking programs like AutoStakkert! and Registax.
+//! Frames are stored as they come from the camera, so Rgb24 is written as BGR.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{BayerPattern, CameraInfo, Frame, FrameFormat, ImgType};
+
+const FILE_ID: &[u8; 14] = b"LUCAM-RECORDER";
+const HEADER_SIZE: u64 = 178;
+const STRING_SIZE: usize = 40;
+/// The timestamps count 100ns since 0001-01-01, this is 1970-01-01.
+const UNIX_EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+const NANOS_PER_TICK: u128 = 100;
+
+/// Offset of the frame count in the header.
+const FRAME_COUNT_OFFSET: u64 = 38;
+/// Offset of the local and the UTC start time in the header.
+const DATE_TIME_OFFSET: u64 = 162;
+const DATE_TIME_UTC_OFFSET: u64 = 170;
+
+/// Layout of the color data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorId {
+    Mono,
+    Bayer(BayerPattern),
+    Rgb,
+    Bgr,
+}
+
+impl ColorId {
+    fn to_raw(self) -> i32 {
+        match self {
+            Self::Mono => 0,
+            Self::Bayer(BayerPattern::Rg) => 8,
+            Self::Bayer(BayerPattern::Gr) => 9,
+            Self::Bayer(BayerPattern::Gb) => 10,
+            Self::Bayer(BayerPattern::Bg) => 11,
+            Self::Rgb => 100,
+            Self::Bgr => 101,
+        }
+    }
+
+    fn from_raw(raw: i32) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Mono),
+            8 => Some(Self::Bayer(BayerPattern::Rg)),
+            9 => Some(Self::Bayer(BayerPattern::Gr)),
+            10 => Some(Self::Bayer(BayerPattern::Gb)),
+            11 => Some(Self::Bayer(BayerPattern::Bg)),
+            100 => Some(Self::Rgb),
+            101 => Some(Self::Bgr),
+            _ => None,
+        }
+    }
+
+    fn planes(self) -> usize {
+        match self {
+            Self::Rgb | Self::Bgr => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// Header of a SER file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerHeader {
+    pub color_id: ColorId,
+    pub width: u32,
+    pub height: u32,
+    /// Bits per color plane, 16 bit data is used above 8.
+    pub pixel_depth: u32,
+    pub frame_count: u32,
+    pub observer: String,
+    pub instrument: String,
+    pub telescope: String,
+    /// Start of the recording, ```None``` if the file doesn't have it.
+    pub date_time_utc: Option<SystemTime>,
+    /// The 16 bit data is in little endian.
+    pub little_endian: bool,
+}
+
+impl SerHeader {
+    fn bytes_per_sample(&self) -> usize {
+        if self.pixel_depth > 8 { 2 } else { 1 }
+    }
+
+    /// Size of a single frame in bytes, ```None``` if it doesn't fit in a ```usize```.
+    pub fn frame_size(&self) -> Option<usize> {
+        (self.width as usize)
+            .checked_mul(self.height as usize)?
+            .checked_mul(self.color_id.planes() * self.bytes_per_sample())
+    }
+
+    /// Format of the frames in the file.
+    pub fn frame_format(&self) -> FrameFormat {
+        let img_type = match (self.color_id.planes(), self.bytes_per_sample()) {
+            (3, _) => ImgType::Rgb24,
+            (_, 2) => ImgType::Raw16,
+            _ => ImgType::Raw8,
+        };
+        FrameFormat {
+            width: self.width,
+            height: self.height,
+            bin: 1,
+            img_type,
+            bayer_pattern: match self.color_id {
+                ColorId::Bayer(bayer_pattern) => Some(bayer_pattern),
+                _ => None,
+            },
+            bit_depth: self.pixel_depth,
+        }
+    }
+}
+
+/// Streams frames into a SER file.
+///
+/// The header is written with the first frame, which also sets the format of the file.
+/// The frame count and the timestamp trailer are written by ```finish``` or when the writer is dropped.
+#[derive(Debug)]
+pub struct SerWriter<W: Write + Seek> {
+    writer: Option<BufWriter<W>>,
+    observer: String,
+    instrument: String,
+    telescope: String,
+    format: Option<FrameFormat>,
+    /// Start time of every frame in ticks.
+    timestamps: Vec<i64>,
+}
+
+impl SerWriter<File> {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(File::create(path)?))
+    }
+}
+
+impl<W: Write + Seek> SerWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Some(BufWriter::new(writer)),
+            observer: String::new(),
+            instrument: String::new(),
+            telescope: String::new(),
+            format: None,
+            timestamps: Vec::new(),
+        }
+    }
+
+    /// Use the name of the camera as instrument.
+    pub fn camera(self, info: &CameraInfo) -> Self {
+        self.instrument(&info.name)
+    }
+
+    /// The header strings have at most 40 characters, longer ones are cut off.
+    pub fn observer(mut self, observer: &str) -> Self {
+        self.observer = observer.to_string();
+        self
+    }
+
+    pub fn instrument(mut self, instrument: &str) -> Self {
+        self.instrument = instrument.to_string();
+        self
+    }
+
+    pub fn telescope(mut self, telescope: &str) -> Self {
+        self.telescope = telescope.to_string();
+        self
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// Append a frame, it's timestamped with the start of its exposure or the current time without metadata.
+    /// All frames need the same width, height and image type.
+    pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let format = *frame.format();
+        match self.format {
+            None => {
+                self.format = Some(format);
+                self.write_header(format)?;
+            }
+            Some(first) if (first.width, first.height, first.img_type) != (format.width, format.height, format.img_type) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("frame {}x{} {:?} doesn't match the SER file {}x{} {:?}", format.width, format.height, format.img_type, first.width, first.height, first.img_type),
+                ));
+            }
+            Some(_) => {}
+        }
+
+        let writer = self.writer.as_mut().unwrap();
+        match frame.as_u16() {
+            Some(pixels) if cfg!(target_endian = "big") => {
+                for pixel in pixels {
+                    writer.write_all(&pixel.to_le_bytes())?;
+                }
+            }
+            _ => writer.write_all(frame.as_u8())?,
+        }
+
+        let time = frame.metadata().map_or_else(SystemTime::now, |metadata| metadata.start.utc);
+        self.timestamps.push(to_ticks(time));
+        Ok(())
+    }
+
+    /// Write the frame count and the timestamps and get the writer back.
+    pub fn finish(mut self) -> io::Result<W> {
+        let result = self.finalize();
+        let writer = self.writer.take().unwrap();
+        result?;
+        writer.into_inner().map_err(io::IntoInnerError::into_error)
+    }
+
+    fn write_header(&mut self, format: FrameFormat) -> io::Result<()> {
+        let color_id = match (format.img_type, format.bayer_pattern) {
+            (ImgType::Rgb24, _) => ColorId::Bgr,
+            (_, Some(bayer_pattern)) => ColorId::Bayer(bayer_pattern),
+            (_, None) => ColorId::Mono,
+        };
+        let pixel_depth: i32 = if format.img_type == ImgType::Raw16 { 16 } else { 8 };
+
+        let writer = self.writer.as_mut().unwrap();
+        writer.write_all(FILE_ID)?;
+        // LuID, unused.
+        writer.write_all(&0i32.to_le_bytes())?;
+        writer.write_all(&color_id.to_raw().to_le_bytes())?;
+        // Most programs read 0 as little endian, against the specification.
+        writer.write_all(&0i32.to_le_bytes())?;
+        writer.write_all(&(format.width as i32).to_le_bytes())?;
+        writer.write_all(&(format.height as i32).to_le_bytes())?;
+        writer.write_all(&pixel_depth.to_le_bytes())?;
+        // The frame count is written by finalize.
+        writer.write_all(&0i32.to_le_bytes())?;
+        for text in [&self.observer, &self.instrument, &self.telescope] {
+            let mut field = [0; STRING_SIZE];
+            let bytes: Vec<u8> = text.chars().map(|c| if c.is_ascii() { c as u8 } else { b'?' }).take(STRING_SIZE).collect();
+            field[..bytes.len()].copy_from_slice(&bytes);
+            writer.write_all(&field)?;
+        }
+        // Local and UTC time of the first frame, both written as UTC by finalize.
+        writer.write_all(&[0; 16])
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        let writer = self.writer.as_mut().unwrap();
+        if self.format.is_some() {
+            for timestamp in &self.timestamps {
+                writer.write_all(&timestamp.to_le_bytes())?;
+            }
+            writer.seek(SeekFrom::Start(FRAME_COUNT_OFFSET))?;
+            writer.write_all(&(self.timestamps.len() as i32).to_le_bytes())?;
+            writer.seek(SeekFrom::Start(DATE_TIME_OFFSET))?;
+            let start = self.timestamps.first().copied().unwrap_or_default();
+            writer.write_all(&start.to_le_bytes())?;
+            writer.write_all(&start.to_le_bytes())?;
+            writer.seek(SeekFrom::End(0))?;
+        }
+        writer.flush()
+    }
+}
+
+impl<W: Write + Seek> Drop for SerWriter<W> {
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.finalize();
+        }
+    }
+}
+
+/// Reads frames and timestamps of a SER file.
+#[derive(Debug)]
+pub struct SerReader<R: Read + Seek> {
+    reader: BufReader<R>,
+    header: SerHeader,
+    /// Timestamps of the frames, empty if the file has no trailer.
+    timestamps: Vec<SystemTime>,
+    frame_size: usize,
+    /// Length of the file, frames past it aren't read.
+    len: u64,
+}
+
+impl SerReader<File> {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> SerReader<R> {
+    pub fn new(reader: R) -> io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let mut raw = [0; HEADER_SIZE as usize];
+        reader.read_exact(&mut raw)?;
+        if &raw[..14] != FILE_ID {
+            return Err(invalid_data("not a SER file"));
+        }
+
+        let int = |offset: usize| i32::from_le_bytes(raw[offset..offset + 4].try_into().unwrap());
+        let text = |offset: usize| {
+            let field = &raw[offset..offset + STRING_SIZE];
+            let end = field.iter().position(|&c| c == 0).unwrap_or(STRING_SIZE);
+            String::from_utf8_lossy(&field[..end]).trim_end().to_string()
+        };
+        let date_time_utc = i64::from_le_bytes(raw[DATE_TIME_UTC_OFFSET as usize..].try_into().unwrap());
+
+        let color_id = ColorId::from_raw(int(18)).ok_or_else(|| invalid_data(format!("unsupported SER color id {}", int(18))))?;
+        let positive = |value: i32, name: &str| u32::try_from(value).map_err(|_| invalid_data(format!("invalid SER {} {}", name, value)));
+        let header = SerHeader {
+            color_id,
+            width: positive(int(26), "width")?,
+            height: positive(int(30), "height")?,
+            pixel_depth: positive(int(34), "pixel depth")?,
+            frame_count: positive(int(38), "frame count")?,
+            observer: text(42),
+            instrument: text(82),
+            telescope: text(122),
+            date_time_utc: if date_time_utc > 0 { from_ticks(date_time_utc) } else { None },
+            little_endian: int(22) == 0,
+        };
+        if !(1..=16).contains(&header.pixel_depth) {
+            return Err(invalid_data(format!("invalid SER pixel depth {}", header.pixel_depth)));
+        }
+        if header.color_id.planes() == 3 && header.bytes_per_sample() == 2 {
+            return Err(invalid_data("16 bit color SER files aren't supported"));
+        }
+
+        let frame_size = header.frame_size().ok_or_else(|| invalid_data(format!("invalid SER frame size {}x{}", header.width, header.height)))?;
+        let frame_count = u64::from(header.frame_count);
+        let len = reader.seek(SeekFrom::End(0))?;
+        let mut timestamps = Vec::new();
+        // A file with frames past its end has no trailer either.
+        let trailer_start = (frame_size as u64).checked_mul(frame_count).and_then(|size| size.checked_add(HEADER_SIZE));
+        if let Some(trailer_start) = trailer_start.filter(|&start| len.saturating_sub(start) >= frame_count * 8) {
+            reader.seek(SeekFrom::Start(trailer_start))?;
+            let mut timestamp = [0; 8];
+            for _ in 0..header.frame_count {
+                reader.read_exact(&mut timestamp)?;
+                let ticks = i64::from_le_bytes(timestamp);
+                timestamps.push(from_ticks(ticks).ok_or_else(|| invalid_data(format!("invalid SER timestamp {}", ticks)))?);
+            }
+        }
+
+        Ok(Self { reader, header, timestamps, frame_size, len })
+    }
+
+    pub fn header(&self) -> &SerHeader {
+        &self.header
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.header.frame_count as usize
+    }
+
+    /// UTC start time of the frame, ```None``` if the file has no timestamps.
+    pub fn timestamp(&self, index: usize) -> Option<SystemTime> {
+        self.timestamps.get(index).copied()
+    }
+
+    /// Read a frame, ```None``` if the index is past the last frame.
+    pub fn read_frame(&mut self, index: usize) -> io::Result<Option<Frame>> {
+        if index >= self.frame_count() {
+            return Ok(None);
+        }
+        // Don't allocate a frame from the header before knowing the file has it.
+        let start = (self.frame_size as u64).saturating_mul(index as u64).saturating_add(HEADER_SIZE);
+        if self.len.saturating_sub(start) < self.frame_size as u64 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("SER frame {} is past the end of the file", index)));
+        }
+        self.reader.seek(SeekFrom::Start(start))?;
+
+        let mut frame = Frame::new(self.header.frame_format());
+        self.reader.read_exact(frame.as_u8_mut())?;
+        let little_endian = self.header.little_endian;
+        if let Some(pixels) = frame.as_u16_mut() {
+            for pixel in pixels {
+                *pixel = if little_endian { u16::from_le(*pixel) } else { u16::from_be(*pixel) };
+            }
+        }
+        if self.header.color_id == ColorId::Rgb {
+            frame.as_u8_mut().chunks_exact_mut(3).for_each(|pixel| pixel.swap(0, 2));
+        }
+        Ok(Some(frame))
+    }
+}
+
+fn to_ticks(time: SystemTime) -> i64 {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    UNIX_EPOCH_TICKS + (since_epoch.as_nanos() / NANOS_PER_TICK) as i64
+}
+
+/// ```None``` if the time can't be represented, times before 1970 are clamped to the epoch.
+fn from_ticks(ticks: i64) -> Option<SystemTime> {
+    let since_epoch = u64::try_from(ticks.checked_sub(UNIX_EPOCH_TICKS)?).unwrap_or_default();
+    UNIX_EPOCH.checked_add(Duration::from_nanos(since_epoch.checked_mul(NANOS_PER_TICK as u64)?))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
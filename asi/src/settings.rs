@@ -0,0 +1,189 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Camera, CameraMode, ControlType, Result, Roi, TrigOutput};
+
+/// Snapshot of everything that can be set on a camera, so it can be put back
+/// into the same state later, eg. the one it was calibrated with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CameraSettings {
+    /// Only available for trigger cameras.
+    pub camera_mode: Option<CameraMode>,
+    pub roi: Roi,
+    /// Only available for trigger cameras.
+    pub trigger_outputs: Vec<TriggerOutputConfig>,
+    /// All writable controls which are settings, ordered by control type, see ```is_setting```.
+    pub controls: Vec<ControlSetting>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ControlSetting {
+    pub control_type: ControlType,
+    /// The value as the SDK reports it, so it's restored unchanged.
+    pub value: i64,
+    pub auto: bool,
+}
+
+/// Configuration of an output pin of the trigger port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TriggerOutputConfig {
+    pub pin: TrigOutput,
+    pub pin_high: bool,
+    pub delay: usize,
+    pub duration: usize,
+}
+
+/// A setting which differs between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SettingChange {
+    /// Name of the setting, eg. ```"bin"``` or ```"Gain"``` for controls.
+    pub setting: String,
+    /// The value in the snapshot ```diff``` was called on, ```"-"``` if it isn't there.
+    pub from: String,
+    /// The value in the other snapshot, ```"-"``` if it isn't there.
+    pub to: String,
+}
+
+impl fmt::Display for SettingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.setting, self.from, self.to)
+    }
+}
+
+impl CameraSettings {
+    /// Read the current settings of the camera.
+    pub fn capture(camera: &Camera) -> Result<Self> {
+        let roi = camera.roi()?;
+
+        let (camera_mode, trigger_outputs) = if camera.info().is_trigger_cam {
+            let mut trigger_outputs = Vec::new();
+            for pin in [TrigOutput::PinA, TrigOutput::PinB] {
+                let (pin_high, delay, duration) = camera.trigger_output_io_conf(pin)?;
+                trigger_outputs.push(TriggerOutputConfig { pin, pin_high, delay, duration });
+            }
+            (Some(camera.camera_mode()?), trigger_outputs)
+        } else {
+            (None, Vec::new())
+        };
+
+        let mut control_types: Vec<_> = camera
+            .controls()
+            .filter(|caps| caps.is_writable && is_setting(caps.control_type))
+            .map(|caps| caps.control_type)
+            .collect();
+        control_types.sort_by_key(|&control_type| u32::from(control_type));
+        let mut controls = Vec::new();
+        for control_type in control_types {
+            let (value, auto) = camera.control_value(control_type)?;
+            controls.push(ControlSetting { control_type, value: value as i64, auto });
+        }
+
+        Ok(Self { camera_mode, roi, trigger_outputs, controls })
+    }
+
+    /// Put the camera into the state of this snapshot.
+    ///
+    /// Running capture is stopped first, then the camera mode and the ROI
+    /// are set, followed by the controls and the trigger outputs.
+    /// Controls which aren't settings are skipped, see ```is_setting```.
+    pub fn apply(&self, camera: &Camera) -> Result<()> {
+        if camera.is_capturing_video() {
+            camera.stop_video_capture()?;
+        }
+        if camera.is_exposing() {
+            camera.stop_exposure()?;
+        }
+
+        if let Some(camera_mode) = self.camera_mode {
+            camera.set_camera_mode(camera_mode)?;
+        }
+        camera.set_roi(&self.roi)?;
+
+        for control in self.controls.iter().filter(|control| is_setting(control.control_type)) {
+            camera.set_raw_control_value(control.control_type, control.value, control.auto)?;
+        }
+        for output in &self.trigger_outputs {
+            camera.set_trigger_output_io_conf(output.pin, output.pin_high, output.delay, output.duration)?;
+        }
+
+        Ok(())
+    }
+
+    /// List the settings which are different in ```other```.
+    pub fn diff(&self, other: &CameraSettings) -> Vec<SettingChange> {
+        let mut changes = Vec::new();
+        let mut compare = |setting: &str, from: String, to: String| {
+            if from != to {
+                changes.push(SettingChange { setting: setting.to_string(), from, to });
+            }
+        };
+
+        compare("camera_mode", or_missing(self.camera_mode.map(|mode| format!("{:?}", mode))), or_missing(other.camera_mode.map(|mode| format!("{:?}", mode))));
+
+        compare("width", self.roi.width.to_string(), other.roi.width.to_string());
+        compare("height", self.roi.height.to_string(), other.roi.height.to_string());
+        compare("bin", self.roi.bin.to_string(), other.roi.bin.to_string());
+        compare("img_type", format!("{:?}", self.roi.img_type), format!("{:?}", other.roi.img_type));
+        compare("start_x", self.roi.start_x.to_string(), other.roi.start_x.to_string());
+        compare("start_y", self.roi.start_y.to_string(), other.roi.start_y.to_string());
+
+        for pin in [TrigOutput::PinA, TrigOutput::PinB] {
+            let find = |settings: &CameraSettings| {
+                settings.trigger_outputs.iter().find(|output| output.pin == pin).map(|output| {
+                    format!("pin_high: {}, delay: {}, duration: {}", output.pin_high, output.delay, output.duration)
+                })
+            };
+            compare(&format!("trigger_output {:?}", pin), or_missing(find(self)), or_missing(find(other)));
+        }
+
+        let mut control_types: Vec<_> = self.controls.iter().chain(&other.controls).map(|control| control.control_type).collect();
+        control_types.sort_by_key(|&control_type| u32::from(control_type));
+        control_types.dedup();
+        for control_type in control_types {
+            let find = |settings: &CameraSettings| {
+                settings.controls.iter().find(|control| control.control_type == control_type).map(|control| {
+                    if control.auto { format!("{} (auto)", control.value) } else { control.value.to_string() }
+                })
+            };
+            compare(&format!("{:?}", control_type), or_missing(find(self)), or_missing(find(other)));
+        }
+
+        changes
+    }
+}
+
+/// Whether a control is part of a snapshot. Writing ```UsbHubReset``` resets the USB hub and the
+/// GPS lines are tied to the current ROI, so they aren't settings, neither are unknown controls.
+pub fn is_setting(control_type: ControlType) -> bool {
+    matches!(
+        control_type,
+        ControlType::Gain
+            | ControlType::Exposure
+            | ControlType::Gamma
+            | ControlType::WbR
+            | ControlType::WbB
+            | ControlType::Offset
+            | ControlType::BandwidthOverflow
+            | ControlType::Overclock
+            | ControlType::Flip
+            | ControlType::AutoMaxGain
+            | ControlType::AutoMaxExp
+            | ControlType::AutoTargetBrightness
+            | ControlType::HardwareBin
+            | ControlType::HighSpeedMode
+            | ControlType::TargetTemp
+            | ControlType::CoolerOn
+            | ControlType::MonoBin
+            | ControlType::FanOn
+            | ControlType::PatternAdjust
+            | ControlType::AntiDewHeater
+            | ControlType::FanAdjust
+            | ControlType::PwrLedBright
+            | ControlType::RollingInterval
+    )
+}
+
+fn or_missing(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "-".to_string())
+}
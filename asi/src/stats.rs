@@ -0,0 +1,234 @@
+use crate::debayer::red_position;
+use crate::{Frame, ImgType};
+
+/// Options of ```Frame::stats_with```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatsOptions {
+    /// Number of histogram bins, they split the range up to the maximal ADU evenly.
+    pub bins: usize,
+    /// Only every n-th pixel of every n-th row is used, 2x2 blocks for bayer data so every color is kept.
+    /// 1 uses all pixels, higher values are faster for live video.
+    pub subsample: usize,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self { bins: 256, subsample: 1 }
+    }
+}
+
+impl StatsOptions {
+    /// Options for live video, using every 4th pixel.
+    pub fn fast() -> Self {
+        Self { subsample: 4, ..Self::default() }
+    }
+}
+
+/// Color channel of the statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// All pixels of a mono frame.
+    Mono,
+    Red,
+    Green,
+    Blue,
+}
+
+/// Histogram over ```0..=max_value```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub counts: Vec<u64>,
+    pub max_value: u16,
+}
+
+impl Histogram {
+    /// The lowest and the highest value which are counted in the bin.
+    pub fn bin_range(&self, bin: usize) -> (u16, u16) {
+        let values = u64::from(self.max_value) + 1;
+        let bins = self.counts.len() as u64;
+        let start = (bin as u64 * values).div_ceil(bins);
+        let end = ((bin as u64 + 1) * values).div_ceil(bins);
+        (start as u16, end.saturating_sub(1) as u16)
+    }
+}
+
+/// Statistics of one channel. Raw16 values are in native ADU, see ```Frame::native_adu```.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelStats {
+    /// Number of pixels used.
+    pub count: usize,
+    pub min: u16,
+    pub max: u16,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    /// Median absolute deviation from the median.
+    pub mad: f64,
+    /// Pixels at the maximal ADU.
+    pub saturated: usize,
+    pub histogram: Histogram,
+}
+
+/// Statistics of all channels of a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameStats {
+    /// ```Channel::Mono``` for mono frames, otherwise red, green and blue.
+    pub channels: Vec<(Channel, ChannelStats)>,
+}
+
+impl FrameStats {
+    pub fn channel(&self, channel: Channel) -> Option<&ChannelStats> {
+        self.channels.iter().find(|(c, _)| *c == channel).map(|(_, stats)| stats)
+    }
+}
+
+impl Frame {
+    /// Statistics of all pixels with a 256 bin histogram.
+    pub fn stats(&self) -> FrameStats {
+        self.stats_with(&StatsOptions::default())
+    }
+
+    pub fn stats_with(&self, options: &StatsOptions) -> FrameStats {
+        let max_value = self.max_adu();
+        let shift = self.adu_shift();
+        let step = options.subsample.max(1);
+        let (width, height) = (self.width() as usize, self.height() as usize);
+
+        let (channels, mut accumulators) = match (self.img_type(), self.bayer_pattern()) {
+            (ImgType::Rgb24, _) | (_, Some(_)) => (
+                vec![Channel::Red, Channel::Green, Channel::Blue],
+                vec![Accumulator::new(max_value), Accumulator::new(max_value), Accumulator::new(max_value)],
+            ),
+            _ => (vec![Channel::Mono], vec![Accumulator::new(max_value)]),
+        };
+
+        match self.img_type() {
+            ImgType::Rgb24 => {
+                let data = self.as_u8();
+                for (x, y) in positions(width, height, step, 1) {
+                    let index = (y * width + x) * 3;
+                    // Blue, green, red order.
+                    for channel in 0..3 {
+                        accumulators[2 - channel].add(data[index + channel].into());
+                    }
+                }
+            }
+            img_type => {
+                let value = |index: usize| match img_type {
+                    ImgType::Raw16 => self.as_u16().unwrap()[index] >> shift,
+                    _ => self.as_u8()[index].into(),
+                };
+                match self.bayer_pattern() {
+                    Some(pattern) => {
+                        let (red_x, red_y) = red_position(pattern);
+                        let (red_x, red_y) = (red_x as usize, red_y as usize);
+                        for (x, y) in positions(width, height, step, 2) {
+                            let channel = match (x % 2 == red_x, y % 2 == red_y) {
+                                (true, true) => 0,
+                                (false, false) => 2,
+                                _ => 1,
+                            };
+                            accumulators[channel].add(value(y * width + x));
+                        }
+                    }
+                    None => {
+                        for (x, y) in positions(width, height, step, 1) {
+                            accumulators[0].add(value(y * width + x));
+                        }
+                    }
+                }
+            }
+        }
+
+        FrameStats {
+            channels: channels.into_iter().zip(accumulators.iter().map(|accumulator| accumulator.finish(options.bins))).collect(),
+        }
+    }
+}
+
+/// Positions of the used pixels. With a block size of 2 every used block has all of its 4 pixels.
+fn positions(width: usize, height: usize, step: usize, block: usize) -> impl Iterator<Item = (usize, usize)> {
+    // Skip to the next used block instead of testing every coordinate.
+    let used = move |size: usize| (0..size).step_by(step.saturating_mul(block)).flat_map(move |start| start..(start + block).min(size));
+    used(height).flat_map(move |y| used(width).map(move |x| (x, y)))
+}
+
+/// Counts every value, so median and MAD are exact without sorting the pixels.
+struct Accumulator {
+    counts: Vec<u64>,
+    sum: f64,
+    sum_of_squares: f64,
+}
+
+impl Accumulator {
+    fn new(max_value: u16) -> Self {
+        Self { counts: vec![0; usize::from(max_value) + 1], sum: 0.0, sum_of_squares: 0.0 }
+    }
+
+    fn add(&mut self, value: u16) {
+        // Values above the maximum only come from data which doesn't match its bit depth.
+        let index = usize::from(value).min(self.counts.len() - 1);
+        self.counts[index] += 1;
+        let value = f64::from(value);
+        self.sum += value;
+        self.sum_of_squares += value * value;
+    }
+
+    fn finish(&self, bins: usize) -> ChannelStats {
+        let count: u64 = self.counts.iter().sum();
+        let max_value = self.counts.len() - 1;
+        let bins = bins.max(1);
+        let mut histogram = vec![0; bins];
+        for (value, &n) in self.counts.iter().enumerate() {
+            histogram[value * bins / (max_value + 1)] += n;
+        }
+        let histogram = Histogram { counts: histogram, max_value: max_value as u16 };
+
+        if count == 0 {
+            return ChannelStats { count: 0, min: 0, max: 0, mean: f64::NAN, median: f64::NAN, stddev: f64::NAN, mad: f64::NAN, saturated: 0, histogram };
+        }
+
+        let mean = self.sum / count as f64;
+        let variance = (self.sum_of_squares / count as f64 - mean * mean).max(0.0);
+        let median = counted_median(self.counts.iter().copied().enumerate(), count);
+
+        // Deviations are counted in half values, since the median can end in .5.
+        let mut deviations = vec![0; self.counts.len() * 2];
+        for (value, &n) in self.counts.iter().enumerate() {
+            deviations[(value as f64 * 2.0 - median * 2.0).abs() as usize] += n;
+        }
+        let mad = counted_median(deviations.into_iter().enumerate(), count) / 2.0;
+
+        ChannelStats {
+            count: count as usize,
+            min: self.counts.iter().position(|&n| n > 0).unwrap() as u16,
+            max: self.counts.iter().rposition(|&n| n > 0).unwrap() as u16,
+            mean,
+            median,
+            stddev: variance.sqrt(),
+            mad,
+            saturated: self.counts[max_value] as usize,
+            histogram,
+        }
+    }
+}
+
+/// Median of values given as ascending ```(value, count)``` pairs, the mean of the two middle values for an even count.
+fn counted_median(counts: impl Iterator<Item = (usize, u64)>, count: u64) -> f64 {
+    let (lower_rank, upper_rank) = ((count - 1) / 2, count / 2);
+    let mut lower = None;
+    let mut seen = 0;
+    for (value, n) in counts {
+        if n == 0 {
+            continue;
+        }
+        seen += n;
+        if lower.is_none() && seen > lower_rank {
+            lower = Some(value);
+        }
+        if seen > upper_rank {
+            return (lower.unwrap() + value) as f64 / 2.0;
+        }
+    }
+    f64::NAN
+}
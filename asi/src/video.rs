@@ -0,0 +1,146 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+use crate::metadata::{FrameControls, CONTROLS_INTERVAL};
+use crate::{Camera, Frame, FrameFormat, Result, Roi};
+
+/// Number of frames which are allocated when a video stream starts.
+const POOL_SIZE: usize = 4;
+
+type Pool = Arc<Mutex<Vec<Frame>>>;
+
+/// Running video capture, created by ```Camera::video_stream```.
+///
+/// Iterating waits for the next frame. Errors are yielded too, including ```ErrorCode::Timeout```
+/// if no frame arrived in time, so the caller decides when to give up.
+/// The capture is stopped when the stream is dropped.
+///
+/// The ROI is only read again after it was changed. The controls in the frame metadata are read
+/// once a second, so values changed in the meantime or auto values may be up to a second old.
+#[derive(Debug)]
+pub struct VideoStream<'a> {
+    camera: &'a Camera,
+    wait_ms: u32,
+    pool: Pool,
+    /// The ROI and the format of its frames, with ```Camera::layout_changes``` when they were read.
+    layout: Option<(u64, FrameFormat, Roi)>,
+    /// The controls for the frame metadata and when they were read.
+    controls: Option<(FrameControls, Instant)>,
+}
+
+/// Frame of a video stream, its buffer goes back to the stream's pool when it's dropped.
+#[derive(Debug)]
+pub struct PooledFrame {
+    frame: Option<Frame>,
+    pool: Pool,
+}
+
+impl Camera {
+    /// Start video capture and get the frames through an iterator, waiting ```wait_ms``` for each one.
+    ///
+    /// The frame buffers are allocated up front and reused, so the small SDK buffer is emptied as fast as possible.
+    /// Keeping more frames alive than the pool holds allocates new ones.
+    pub fn video_stream(&self, wait_ms: u32) -> Result<VideoStream<'_>> {
+        let format = self.frame_format()?;
+        let pool = (0..POOL_SIZE).map(|_| Frame::new(format)).collect();
+        self.start_video_capture()?;
+        Ok(VideoStream {
+            camera: self,
+            wait_ms,
+            pool: Arc::new(Mutex::new(pool)),
+            layout: None,
+            controls: None,
+        })
+    }
+}
+
+impl VideoStream<'_> {
+    pub fn camera(&self) -> &Camera {
+        self.camera
+    }
+
+    /// Frames dropped by the SDK since the capture started, read from the camera.
+    pub fn dropped_frames(&self) -> Result<u32> {
+        self.camera.get_dropped_frames()
+    }
+
+    pub(crate) fn next_frame(&mut self) -> Result<PooledFrame> {
+        // The format stays locked until the download is done, so the cached layout can't get outdated.
+        let _format = self.camera.lock_format();
+        let layout_changes = self.camera.layout_changes();
+        let (format, roi) = match self.layout {
+            Some((changes, format, roi)) if changes == layout_changes => (format, roi),
+            _ => {
+                let roi = self.camera.roi()?;
+                let format = self.camera.roi_frame_format(&roi)?;
+                self.layout = Some((layout_changes, format, roi));
+                (format, roi)
+            }
+        };
+        let controls = match self.controls {
+            Some((controls, read)) if read.elapsed() < CONTROLS_INTERVAL => controls,
+            _ => self.controls.insert((self.camera.frame_controls(), Instant::now())).0,
+        };
+
+        let mut frame = self.pool.lock().unwrap_or_else(PoisonError::into_inner).pop().unwrap_or_else(|| Frame::new(format));
+        let frame = match self.camera.video_frame_with(&mut frame, self.wait_ms, format, roi, controls) {
+            Ok(()) => frame,
+            Err(error) => {
+                // Eg. a timeout, the buffer is used for the next frame.
+                self.pool.lock().unwrap_or_else(PoisonError::into_inner).push(frame);
+                return Err(error);
+            }
+        };
+
+        Ok(PooledFrame {
+            frame: Some(frame),
+            pool: self.pool.clone(),
+        })
+    }
+}
+
+impl Iterator for VideoStream<'_> {
+    type Item = Result<PooledFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_frame())
+    }
+}
+
+impl Drop for VideoStream<'_> {
+    fn drop(&mut self) {
+        if self.camera.is_capturing_video() {
+            let _ = self.camera.stop_video_capture();
+        }
+    }
+}
+
+impl PooledFrame {
+    /// Take the frame out of the pool.
+    pub fn into_frame(mut self) -> Frame {
+        self.frame.take().unwrap()
+    }
+}
+
+impl Deref for PooledFrame {
+    type Target = Frame;
+
+    fn deref(&self) -> &Frame {
+        self.frame.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledFrame {
+    fn deref_mut(&mut self) -> &mut Frame {
+        self.frame.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledFrame {
+    fn drop(&mut self) {
+        if let Some(frame) = self.frame.take() {
+            self.pool.lock().unwrap_or_else(PoisonError::into_inner).push(frame);
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender, SendTimeoutError, TrySendError};
+
+use crate::metadata::{FrameControls, CONTROLS_INTERVAL};
+use crate::{Camera, Error, ErrorCode, Frame, FrameFormat, Result, Roi};
+
+/// How long a blocked send waits before commands are handled again.
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Time over which the frame rate is averaged.
+const FPS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What happens to a new frame when the channel is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OverflowPolicy {
+    /// Remove the oldest queued frame to make room, the consumer always gets the latest frames.
+    #[default]
+    DropOldest,
+    /// Discard the new frame.
+    DropNewest,
+    /// Wait until the consumer takes a frame, the SDK may drop frames instead.
+    Block,
+}
+
+/// Configuration of a ```CaptureWorker```.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaptureConfig {
+    /// How long to wait for each video frame.
+    pub wait_ms: u32,
+    /// Number of frames the channel holds, at least 1.
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self { wait_ms: 500, capacity: 8, overflow: OverflowPolicy::default() }
+    }
+}
+
+/// Live statistics of a ```CaptureWorker```.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CaptureStats {
+    /// Frames per second received from the camera, averaged over the last second.
+    pub fps: f64,
+    /// Frames received from the camera.
+    pub frames: u64,
+    /// Frames dropped by the SDK, from ```Camera::get_dropped_frames```.
+    pub dropped_frames: u32,
+    /// Frames discarded because the channel was full.
+    pub overflow_frames: u64,
+    /// Frames waiting in the channel.
+    pub queued: usize,
+    pub capacity: usize,
+}
+
+/// What the capture thread returns, ```None``` if it never got its state.
+type ThreadResult = Option<(Camera, Result<()>)>;
+
+enum Command {
+    StartPosition(u32, u32, Sender<Result<()>>),
+    Gain(i32, Sender<Result<()>>),
+    Exposure(Duration, Sender<Result<()>>),
+    Stop,
+}
+
+impl std::fmt::Debug for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StartPosition(x, y, _) => write!(f, "StartPosition({}, {})", x, y),
+            Self::Gain(gain, _) => write!(f, "Gain({})", gain),
+            Self::Exposure(exposure, _) => write!(f, "Exposure({:?})", exposure),
+            Self::Stop => write!(f, "Stop"),
+        }
+    }
+}
+
+/// Video capture on a dedicated thread which owns the camera.
+///
+/// Frames are delivered through a bounded channel, so processing never delays ```Camera::get_video_data```.
+/// The capture is stopped when the worker is dropped.
+///
+/// The ROI is only read when the capture starts and after ```set_start_position```. The controls in the
+/// frame metadata are read after ```set_gain``` and ```set_exposure``` and otherwise once a second,
+/// so auto values may be up to a second old.
+#[derive(Debug)]
+pub struct CaptureWorker {
+    frames: Receiver<Frame>,
+    recycle: Sender<Frame>,
+    commands: Sender<Command>,
+    stats: Arc<Mutex<CaptureStats>>,
+    thread: Option<JoinHandle<ThreadResult>>,
+}
+
+impl CaptureWorker {
+    /// Start video capture on a new thread.
+    ///
+    /// If the thread or the capture can't be started, the camera is returned with the error.
+    // Boxing the camera would only make getting it back more awkward, the error is rare.
+    #[allow(clippy::result_large_err)]
+    pub fn start(camera: Camera, mut config: CaptureConfig) -> Result<Self, (Camera, Error)> {
+        // A channel without capacity would never take a frame without a waiting consumer.
+        config.capacity = config.capacity.max(1);
+
+        // The state is sent after spawning, so the camera isn't lost if spawning fails.
+        let (state_sender, state_receiver) = crossbeam_channel::bounded::<WorkerState>(1);
+        let (started_sender, started) = crossbeam_channel::bounded(1);
+        let thread = thread::Builder::new()
+            .name("asi-capture".into())
+            .spawn(move || state_receiver.recv().ok().map(|state| state.run(started_sender)));
+        let thread = match thread {
+            Ok(thread) => thread,
+            Err(error) => return Err((camera, Error::SpawnFailed(error.to_string()))),
+        };
+
+        let (frame_sender, frames) = crossbeam_channel::bounded(config.capacity);
+        let (recycle, recycled) = crossbeam_channel::unbounded();
+        let (commands, command_receiver) = crossbeam_channel::unbounded();
+        let stats = Arc::new(Mutex::new(CaptureStats { capacity: config.capacity, ..Default::default() }));
+
+        let state = WorkerState {
+            camera,
+            config,
+            frame_sender,
+            frame_receiver: frames.clone(),
+            recycle: recycle.clone(),
+            recycled,
+            commands: command_receiver,
+            stats: stats.clone(),
+            stopped: false,
+            layout: None,
+            controls: None,
+        };
+        // The thread is waiting for the state, so it's still there to receive it.
+        let _ = state_sender.send(state);
+
+        let mut worker = Self { frames, recycle, commands, stats, thread: Some(thread) };
+        match started.recv() {
+            Ok(Ok(())) => Ok(worker),
+            Ok(Err(error)) => match worker.join() {
+                Some((camera, _)) => Err((camera, error)),
+                None => unreachable!("the capture thread received its state"),
+            },
+            // The thread panicked, joining resumes the panic.
+            Err(_) => {
+                worker.join();
+                unreachable!("the capture thread stopped without reporting the start")
+            }
+        }
+    }
+
+    /// Channel of the captured frames.
+    /// It's disconnected when the capture stopped, ```stop``` returns the reason.
+    pub fn frames(&self) -> &Receiver<Frame> {
+        &self.frames
+    }
+
+    /// Give a processed frame back so its buffer is reused.
+    pub fn recycle(&self, frame: Frame) {
+        let _ = self.recycle.send(frame);
+    }
+
+    pub fn stats(&self) -> CaptureStats {
+        let mut stats = *self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        stats.queued = self.frames.len();
+        stats
+    }
+
+    /// Whether the capture thread is still running.
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Move the ROI while streaming.
+    pub fn set_start_position(&self, start_x: u32, start_y: u32) -> Result<()> {
+        self.command(|reply| Command::StartPosition(start_x, start_y, reply))
+    }
+
+    pub fn set_gain(&self, gain: i32) -> Result<()> {
+        self.command(|reply| Command::Gain(gain, reply))
+    }
+
+    pub fn set_exposure(&self, exposure: Duration) -> Result<()> {
+        self.command(|reply| Command::Exposure(exposure, reply))
+    }
+
+    /// Stop the capture and get the camera back, together with the error which ended the capture if any.
+    pub fn stop(mut self) -> (Camera, Result<()>) {
+        self.join().expect("capture thread already joined")
+    }
+
+    /// Send a command and wait until the capture thread ran it.
+    fn command(&self, command: impl FnOnce(Sender<Result<()>>) -> Command) -> Result<()> {
+        let (reply, result) = crossbeam_channel::bounded(1);
+        self.commands.send(command(reply)).map_err(|_| Error::CaptureStopped)?;
+        result.recv().map_err(|_| Error::CaptureStopped)?
+    }
+
+    fn join(&mut self) -> ThreadResult {
+        let thread = self.thread.take()?;
+        let _ = self.commands.send(Command::Stop);
+        match thread.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+impl Drop for CaptureWorker {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+struct WorkerState {
+    camera: Camera,
+    config: CaptureConfig,
+    frame_sender: Sender<Frame>,
+    /// Used to remove the oldest frame with ```OverflowPolicy::DropOldest```.
+    frame_receiver: Receiver<Frame>,
+    /// Takes back the frames discarded because the channel was full.
+    recycle: Sender<Frame>,
+    recycled: Receiver<Frame>,
+    commands: Receiver<Command>,
+    stats: Arc<Mutex<CaptureStats>>,
+    stopped: bool,
+    /// The ROI and the format of its frames. The worker owns the camera, so only its commands change them.
+    layout: Option<(FrameFormat, Roi)>,
+    /// The controls for the frame metadata and when they were read.
+    controls: Option<(FrameControls, Instant)>,
+}
+
+impl WorkerState {
+    fn run(mut self, started: Sender<Result<()>>) -> (Camera, Result<()>) {
+        if let Err(error) = self.camera.start_video_capture() {
+            let _ = started.send(Err(error.clone()));
+            return (self.camera, Err(error));
+        }
+        let _ = started.send(Ok(()));
+
+        let result = self.capture();
+        let stopped = self.camera.stop_video_capture();
+        (self.camera, result.and(stopped))
+    }
+
+    fn capture(&mut self) -> Result<()> {
+        let mut fps_start = Instant::now();
+        let mut fps_frames = 0;
+        // Kept when no frame arrived in time, so a timeout doesn't cost a buffer.
+        let mut spare = None;
+
+        while !self.stopped {
+            self.handle_commands();
+
+            let (format, roi) = match self.layout {
+                Some(layout) => layout,
+                None => {
+                    let roi = self.camera.roi()?;
+                    *self.layout.insert((self.camera.roi_frame_format(&roi)?, roi))
+                }
+            };
+            let controls = match self.controls {
+                Some((controls, read)) if read.elapsed() < CONTROLS_INTERVAL => controls,
+                _ => self.controls.insert((self.camera.frame_controls(), Instant::now())).0,
+            };
+
+            let mut frame = spare.take().or_else(|| self.recycled.try_recv().ok()).unwrap_or_else(|| Frame::new(format));
+            match self.camera.video_frame_with(&mut frame, self.config.wait_ms, format, roi, controls) {
+                Ok(()) => {}
+                Err(error) if error.code() == Some(ErrorCode::Timeout) => {
+                    spare = Some(frame);
+                    continue;
+                }
+                Err(error) => return Err(error),
+            }
+
+            let dropped_frames = self.camera.get_dropped_frames()?;
+            fps_frames += 1;
+            let elapsed = fps_start.elapsed();
+            self.update_stats(|stats| {
+                stats.frames += 1;
+                stats.dropped_frames = dropped_frames;
+                if elapsed >= FPS_INTERVAL {
+                    stats.fps = fps_frames as f64 / elapsed.as_secs_f64();
+                }
+            });
+            if elapsed >= FPS_INTERVAL {
+                fps_start = Instant::now();
+                fps_frames = 0;
+            }
+
+            self.deliver(frame);
+        }
+        Ok(())
+    }
+
+    fn deliver(&mut self, mut frame: Frame) {
+        loop {
+            match self.config.overflow {
+                OverflowPolicy::Block => match self.frame_sender.send_timeout(frame, BLOCK_POLL_INTERVAL) {
+                    Err(SendTimeoutError::Timeout(returned)) => frame = returned,
+                    _ => return,
+                },
+                _ => match self.frame_sender.try_send(frame) {
+                    Err(TrySendError::Full(returned)) => frame = returned,
+                    _ => return,
+                },
+            }
+
+            if self.config.overflow == OverflowPolicy::DropNewest {
+                self.update_stats(|stats| stats.overflow_frames += 1);
+                let _ = self.recycle.send(frame);
+                return;
+            }
+            if self.config.overflow == OverflowPolicy::DropOldest {
+                if let Ok(oldest) = self.frame_receiver.try_recv() {
+                    self.update_stats(|stats| stats.overflow_frames += 1);
+                    let _ = self.recycle.send(oldest);
+                }
+            }
+            // Commands are still handled while waiting for the consumer.
+            self.handle_commands();
+            if self.stopped {
+                return;
+            }
+        }
+    }
+
+    fn handle_commands(&mut self) {
+        while let Ok(command) = self.commands.try_recv() {
+            let (result, reply) = match command {
+                Command::StartPosition(start_x, start_y, reply) => {
+                    // The start position also shifts the bayer pattern.
+                    self.layout = None;
+                    (self.camera.set_start_position(start_x, start_y), reply)
+                }
+                Command::Gain(gain, reply) => {
+                    self.controls = None;
+                    (self.camera.set_gain(gain), reply)
+                }
+                Command::Exposure(exposure, reply) => {
+                    self.controls = None;
+                    (self.camera.set_exposure(exposure), reply)
+                }
+                Command::Stop => {
+                    self.stopped = true;
+                    continue;
+                }
+            };
+            let _ = reply.send(result);
+        }
+    }
+
+    fn update_stats(&self, update: impl FnOnce(&mut CaptureStats)) {
+        update(&mut self.stats.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+}
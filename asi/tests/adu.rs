@@ -0,0 +1,41 @@
+use asi::{Frame, ImgType};
+
+mod common;
+
+fn raw16(bit_depth: u32, values: &[u16]) -> Frame {
+    let mut frame = Frame::new(common::format(values.len() as u32, 1, ImgType::Raw16, None, bit_depth));
+    frame.as_u16_mut().unwrap().copy_from_slice(values);
+    frame
+}
+
+#[test]
+fn twelve_bit_data() {
+    let frame = raw16(12, &[0, 0x0010, 0x8000, 0xfff0]);
+    assert_eq!(frame.adu_shift(), 4);
+    assert_eq!(frame.max_adu(), 4095);
+    assert_eq!(frame.saturation_level(), 0xfff0);
+    assert_eq!(frame.native_adu().unwrap().collect::<Vec<_>>(), [0, 1, 2048, 4095]);
+    assert_eq!(frame.full_scale().unwrap().collect::<Vec<_>>(), [0, 16, 32776, 65535]);
+    assert_eq!(frame.saturated_pixels(), 1);
+    assert_eq!(frame.electrons(0.25).unwrap().collect::<Vec<_>>(), [0.0, 0.25, 512.0, 1023.75]);
+}
+
+#[test]
+fn sixteen_bit_data() {
+    let frame = raw16(16, &[1, 65534, 65535]);
+    assert_eq!(frame.adu_shift(), 0);
+    assert_eq!(frame.max_adu(), 65535);
+    assert_eq!(frame.native_adu().unwrap().collect::<Vec<_>>(), [1, 65534, 65535]);
+    assert_eq!(frame.full_scale().unwrap().collect::<Vec<_>>(), [1, 65534, 65535]);
+    assert_eq!(frame.saturated_pixels(), 1);
+}
+
+#[test]
+fn eight_bit_data() {
+    let frame = Frame::from_bytes(common::format(3, 1, ImgType::Raw8, None, 12), &[0, 255, 255]).unwrap();
+    assert_eq!(frame.adu_shift(), 0);
+    assert_eq!(frame.saturation_level(), 255);
+    assert_eq!(frame.saturated_pixels(), 2);
+    assert!(frame.native_adu().is_none());
+    assert!(frame.electrons(1.0).is_none());
+}
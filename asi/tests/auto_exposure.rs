@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use asi::{AutoExposure, AutoExposureStep, Error};
+
+const LIMITS: (Duration, Duration) = (Duration::from_micros(32), Duration::from_secs(2));
+
+fn step(exposure: Duration, gain: i32, median: f64) -> AutoExposureStep {
+    AutoExposureStep { exposure, gain, median }
+}
+
+#[test]
+fn correction() {
+    let auto_exposure = AutoExposure::new(0.5);
+    assert_eq!(auto_exposure.correction(0.25), 2.0);
+    assert_eq!(auto_exposure.correction(1.0), 0.1);
+    assert_eq!(auto_exposure.correction(0.99), 0.1);
+    assert_eq!(auto_exposure.correction(0.0), 10.0);
+    assert_eq!(auto_exposure.correction(0.001), 10.0);
+    assert_eq!(auto_exposure.correction(0.9), 0.5 / 0.9);
+}
+
+#[test]
+fn next_exposure() {
+    let auto_exposure = AutoExposure::new(0.5);
+    let next = auto_exposure.next_settings(&step(Duration::from_millis(100), 50, 0.25), LIMITS, Some((0, 500)));
+    assert_eq!(next, (Duration::from_millis(200), 50));
+
+    // Saturated and dark frames change the exposure by at most 10 times.
+    let next = auto_exposure.next_settings(&step(Duration::from_millis(100), 50, 1.0), LIMITS, None);
+    assert_eq!(next, (Duration::from_millis(10), 50));
+    let next = auto_exposure.next_settings(&step(Duration::from_millis(100), 50, 0.0), LIMITS, None);
+    assert_eq!(next, (Duration::from_secs(1), 50));
+}
+
+#[test]
+fn gain_takes_the_rest() {
+    let auto_exposure = AutoExposure::new(0.5);
+    // 10 times brighter, but only 2 times from the exposure, 5 times is 14 dB.
+    let dark = step(Duration::from_secs(1), 50, 0.05);
+    assert_eq!(auto_exposure.next_settings(&dark, LIMITS, Some((0, 500))), (Duration::from_secs(2), 190));
+    assert_eq!(auto_exposure.next_settings(&dark, LIMITS, Some((0, 100))), (Duration::from_secs(2), 100));
+    assert_eq!(auto_exposure.next_settings(&dark, LIMITS, None), (Duration::from_secs(2), 50));
+
+    // At the shortest exposure the gain is lowered.
+    let bright = step(Duration::from_micros(32), 50, 1.0);
+    assert_eq!(auto_exposure.next_settings(&bright, LIMITS, Some((0, 500))), (Duration::from_micros(32), 0));
+}
+
+#[test]
+fn validate() {
+    assert!(AutoExposure::new(0.5).validate().is_ok());
+    assert!(AutoExposure::new(1.0).validate().is_ok());
+    for target in [0.0, -0.5, 1.5, f64::NAN, f64::INFINITY] {
+        assert!(matches!(AutoExposure::new(target).validate(), Err(Error::InvalidAutoExposure(_))), "{}", target);
+    }
+    for tolerance in [-0.1, f64::NAN] {
+        let auto_exposure = AutoExposure { tolerance, ..AutoExposure::new(0.5) };
+        assert!(matches!(auto_exposure.validate(), Err(Error::InvalidAutoExposure(_))), "{}", tolerance);
+    }
+}
+
+#[test]
+fn invalid_target_keeps_the_settings() {
+    let auto_exposure = AutoExposure::new(f64::NAN);
+    let next = auto_exposure.next_settings(&step(Duration::from_millis(100), 50, 0.25), LIMITS, Some((0, 500)));
+    assert_eq!(next, (Duration::from_millis(100), 50));
+}
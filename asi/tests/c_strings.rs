@@ -0,0 +1,45 @@
+use asi::{CameraInfo, ControlCaps, ControlType};
+use asi_sys::*;
+use common::fill;
+
+mod common;
+
+#[test]
+fn camera_name_stops_at_nul() {
+    let mut info = ASI_CAMERA_INFO::default();
+    fill(&mut info.Name, b"ZWO ASI294MC Pro\0garbage");
+    info.SupportedVideoFormat = [0, 2, -1, 0, 0, 0, 0, 0];
+
+    let info = CameraInfo::from(info);
+    assert_eq!(info.name, "ZWO ASI294MC Pro");
+    assert_eq!(info.supported_video_formats.len(), 2);
+}
+
+#[test]
+fn camera_name_without_nul_uses_whole_array() {
+    let mut info = ASI_CAMERA_INFO::default();
+    fill(&mut info.Name, &[b'A'; 64]);
+
+    assert_eq!(CameraInfo::from(info).name, "A".repeat(64));
+}
+
+#[test]
+fn invalid_utf8_is_replaced() {
+    let mut info = ASI_CAMERA_INFO::default();
+    fill(&mut info.Name, b"ZWO \xff\xfe\0");
+
+    assert_eq!(CameraInfo::from(info).name, "ZWO \u{fffd}\u{fffd}");
+}
+
+#[test]
+fn control_caps_strings() {
+    let mut caps = ASI_CONTROL_CAPS::default();
+    fill(&mut caps.Name, b"Gain\0");
+    fill(&mut caps.Description, b"Gain\0");
+    caps.ControlType = ASI_CONTROL_TYPE_ASI_GAIN;
+
+    let caps = ControlCaps::from(caps);
+    assert_eq!(caps.name, "Gain");
+    assert_eq!(caps.description, "Gain");
+    assert_eq!(caps.control_type, ControlType::Gain);
+}
@@ -0,0 +1,52 @@
+use asi::{BayerPattern, CameraInfo, CameraMode, ImgType, SupportedMode, UnknownValue};
+use asi_sys::*;
+
+mod common;
+
+#[test]
+fn converts_sdk_camera_info() {
+    let mut raw = ASI_CAMERA_INFO::default();
+    common::fill(&mut raw.Name, b"ZWO ASI294MC Pro");
+    raw.MaxWidth = 4144;
+    raw.MaxHeight = 2822;
+    raw.IsColorCam = 1;
+    raw.BayerPattern = 0;
+    raw.SupportedBins[..4].copy_from_slice(&[1, 2, 3, 4]);
+    raw.SupportedVideoFormat[..3].copy_from_slice(&[0, 2, -1]);
+    raw.BitDepth = 14;
+
+    let info = CameraInfo::from(raw);
+    assert_eq!(info.name, "ZWO ASI294MC Pro");
+    assert_eq!((info.max_width, info.max_height), (4144, 2822));
+    assert!(info.is_color_cam);
+    assert_eq!(info.bayer_pattern, BayerPattern::Rg);
+    assert_eq!(info.supported_bins, [1, 2, 3, 4]);
+    assert_eq!(info.supported_video_formats, [ImgType::Raw8, ImgType::Raw16]);
+    assert_eq!(info.bit_depth, 14);
+    assert!(info.unknown_values.is_empty());
+}
+
+#[test]
+fn records_unknown_camera_info_values() {
+    let mut raw = ASI_CAMERA_INFO { BayerPattern: 7, ..Default::default() };
+    raw.SupportedVideoFormat[..4].copy_from_slice(&[0, 9, 3, -1]);
+
+    let info = CameraInfo::from(raw);
+    assert_eq!(info.bayer_pattern, BayerPattern::Rg);
+    assert_eq!(info.supported_video_formats, [ImgType::Raw8, ImgType::Y8]);
+    assert_eq!(
+        info.unknown_values,
+        [UnknownValue { kind: "bayer pattern", value: 7 }, UnknownValue { kind: "image type", value: 9 }]
+    );
+}
+
+#[test]
+fn records_unknown_camera_modes() {
+    let mut raw = ASI_SUPPORTED_MODE::default();
+    raw.SupportedCameraMode[..4].copy_from_slice(&[0, 42, 1, -1]);
+    raw.SupportedCameraMode[4] = 2;
+
+    let mode = SupportedMode::from(raw);
+    assert_eq!(mode.camera_mode, [CameraMode::Normal, CameraMode::SoftEdge]);
+    assert_eq!(mode.unknown_values, [UnknownValue { kind: "camera mode", value: 42 }]);
+}
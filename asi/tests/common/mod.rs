@@ -0,0 +1,26 @@
+//! Helpers shared by the integration tests.
+
+// Every test uses only some of them.
+#![allow(dead_code)]
+
+use std::ffi::c_char;
+
+use asi::{BayerPattern, Frame, FrameFormat, ImgType};
+
+/// Format of unbinned frames.
+pub fn format(width: u32, height: u32, img_type: ImgType, bayer_pattern: Option<BayerPattern>, bit_depth: u32) -> FrameFormat {
+    FrameFormat { width, height, bin: 1, img_type, bayer_pattern, bit_depth }
+}
+
+/// A frame filled with a pattern, frames with another ```seed``` have different data.
+pub fn frame(format: FrameFormat, seed: usize) -> Frame {
+    let data: Vec<u8> = (0..format.buffer_size()).map(|i| (i * 37 + seed * 71 + 3) as u8).collect();
+    Frame::from_bytes(format, &data).unwrap()
+}
+
+/// Copy bytes into a string array of the SDK, the rest of the array is left as it is.
+pub fn fill(dst: &mut [c_char], src: &[u8]) {
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d = s as _;
+    }
+}
@@ -0,0 +1,49 @@
+use asi::ControlType;
+use asi_sys::*;
+
+const CONTROL_TYPES: [(ControlType, ASI_CONTROL_TYPE); 29] = [
+    (ControlType::Gain, ASI_CONTROL_TYPE_ASI_GAIN),
+    (ControlType::Exposure, ASI_CONTROL_TYPE_ASI_EXPOSURE),
+    (ControlType::Gamma, ASI_CONTROL_TYPE_ASI_GAMMA),
+    (ControlType::WbR, ASI_CONTROL_TYPE_ASI_WB_R),
+    (ControlType::WbB, ASI_CONTROL_TYPE_ASI_WB_B),
+    (ControlType::Offset, ASI_CONTROL_TYPE_ASI_OFFSET),
+    (ControlType::BandwidthOverflow, ASI_CONTROL_TYPE_ASI_BANDWIDTHOVERLOAD),
+    (ControlType::Overclock, ASI_CONTROL_TYPE_ASI_OVERCLOCK),
+    (ControlType::Temperature, ASI_CONTROL_TYPE_ASI_TEMPERATURE),
+    (ControlType::Flip, ASI_CONTROL_TYPE_ASI_FLIP),
+    (ControlType::AutoMaxGain, ASI_CONTROL_TYPE_ASI_AUTO_MAX_GAIN),
+    (ControlType::AutoMaxExp, ASI_CONTROL_TYPE_ASI_AUTO_MAX_EXP),
+    (ControlType::AutoTargetBrightness, ASI_CONTROL_TYPE_ASI_AUTO_TARGET_BRIGHTNESS),
+    (ControlType::HardwareBin, ASI_CONTROL_TYPE_ASI_HARDWARE_BIN),
+    (ControlType::HighSpeedMode, ASI_CONTROL_TYPE_ASI_HIGH_SPEED_MODE),
+    (ControlType::CoolerPowerPerc, ASI_CONTROL_TYPE_ASI_COOLER_POWER_PERC),
+    (ControlType::TargetTemp, ASI_CONTROL_TYPE_ASI_TARGET_TEMP),
+    (ControlType::CoolerOn, ASI_CONTROL_TYPE_ASI_COOLER_ON),
+    (ControlType::MonoBin, ASI_CONTROL_TYPE_ASI_MONO_BIN),
+    (ControlType::FanOn, ASI_CONTROL_TYPE_ASI_FAN_ON),
+    (ControlType::PatternAdjust, ASI_CONTROL_TYPE_ASI_PATTERN_ADJUST),
+    (ControlType::AntiDewHeater, ASI_CONTROL_TYPE_ASI_ANTI_DEW_HEATER),
+    (ControlType::FanAdjust, ASI_CONTROL_TYPE_ASI_FAN_ADJUST),
+    (ControlType::PwrLedBright, ASI_CONTROL_TYPE_ASI_PWRLED_BRIGNT),
+    (ControlType::UsbHubReset, ASI_CONTROL_TYPE_ASI_USBHUB_RESET),
+    (ControlType::GpsSupport, ASI_CONTROL_TYPE_ASI_GPS_SUPPORT),
+    (ControlType::GpsStartLine, ASI_CONTROL_TYPE_ASI_GPS_START_LINE),
+    (ControlType::GpsEndLine, ASI_CONTROL_TYPE_ASI_GPS_END_LINE),
+    (ControlType::RollingInterval, ASI_CONTROL_TYPE_ASI_ROLLING_INTERVAL),
+];
+
+#[test]
+fn control_types_match_header() {
+    for (control_type, raw) in CONTROL_TYPES {
+        assert_eq!(u32::from(control_type), raw, "{:?}", control_type);
+        assert_eq!(ControlType::from(raw), control_type);
+    }
+}
+
+#[test]
+fn unknown_control_type_round_trips() {
+    let raw = ASI_CONTROL_TYPE_ASI_ROLLING_INTERVAL + 1;
+    assert_eq!(ControlType::from(raw), ControlType::Unknown(raw));
+    assert_eq!(u32::from(ControlType::Unknown(raw)), raw);
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use asi::{Celsius, ControlCaps, ControlType, ControlValue, Error, WhiteBalance};
+
+fn exposure_caps() -> ControlCaps {
+    ControlCaps {
+        name: "Exposure".to_string(),
+        description: "Exposure Time(us)".to_string(),
+        max_value: 2_000_000_000,
+        min_value: 32,
+        default_value: 10_000,
+        is_auto_supported: true,
+        is_writable: true,
+        control_type: ControlType::Exposure,
+    }
+}
+
+#[test]
+fn temperatures() {
+    assert_eq!(Celsius::from_tenths(-153), Celsius(-15.3));
+    assert_eq!(Celsius::from_tenths(250), Celsius(25.0));
+    assert_eq!(Celsius(-9.6).whole_degrees(), -10);
+    assert_eq!(Celsius(4.4).whole_degrees(), 4);
+    assert_eq!(Celsius(-15.25).to_string(), "-15.2 °C");
+}
+
+#[test]
+fn white_balance_channels() {
+    let white_balance = ControlValue::from_channels(ControlValue::manual(52), ControlValue::auto(95));
+    assert_eq!(white_balance, ControlValue::auto(WhiteBalance { red: 52, blue: 95 }));
+    assert_eq!(white_balance.channels(), (ControlValue::auto(52), ControlValue::auto(95)));
+    let manual = ControlValue::manual(WhiteBalance { red: 1, blue: 2 });
+    assert_eq!(manual.channels(), (ControlValue::manual(1), ControlValue::manual(2)));
+}
+
+#[test]
+fn exposure_in_microseconds() {
+    assert_eq!(ControlValue::from_micros(ControlValue::auto(1500)), ControlValue::auto(Duration::from_micros(1500)));
+    assert_eq!(ControlValue::from_micros(ControlValue::manual(-1)), ControlValue::manual(Duration::ZERO));
+    assert_eq!(ControlValue::manual(Duration::from_nanos(2_500_900)).micros(), ControlValue::manual(2500));
+    assert_eq!(ControlValue::manual(Duration::MAX).micros(), ControlValue::manual(i64::MAX));
+}
+
+#[test]
+fn long_exposures_are_out_of_range() {
+    let caps = exposure_caps();
+    assert_eq!(caps.validate(10_000, true), Ok(10_000));
+    let forty_minutes = ControlValue::manual(Duration::from_secs(40 * 60)).micros().value;
+    assert_eq!(
+        caps.validate(forty_minutes, false),
+        Err(Error::ControlOutOfRange { control_type: ControlType::Exposure, value: 2_400_000_000, min: 32, max: 2_000_000_000 })
+    );
+    assert!(matches!(caps.validate(i64::MAX, false), Err(Error::ControlOutOfRange { .. })));
+    assert!(matches!(caps.validate(31, false), Err(Error::ControlOutOfRange { .. })));
+}
+
+#[test]
+fn validates_writable_and_auto() {
+    let caps = ControlCaps { is_auto_supported: false, ..exposure_caps() };
+    assert_eq!(caps.validate(100, true), Err(Error::AutoNotSupported(ControlType::Exposure)));
+    let caps = ControlCaps { is_writable: false, ..exposure_caps() };
+    assert_eq!(caps.validate(100, false), Err(Error::ControlNotWritable(ControlType::Exposure)));
+}
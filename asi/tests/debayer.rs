@@ -0,0 +1,128 @@
+use asi::debayer::{debayer, effective_pattern, Algorithm, ColorImage, RgbImage};
+use asi::{BayerPattern, FlipStatus, Frame, FrameFormat, ImgType};
+
+mod common;
+
+const PATTERNS: [BayerPattern; 4] = [BayerPattern::Rg, BayerPattern::Bg, BayerPattern::Gr, BayerPattern::Gb];
+const ALGORITHMS: [Algorithm; 3] = [Algorithm::Superpixel, Algorithm::Bilinear, Algorithm::MalvarHeCutler];
+
+/// Sample an RGB image like a sensor with the pattern would.
+fn mosaic(width: u32, height: u32, pattern: BayerPattern, rgb: impl Fn(u32, u32) -> [u16; 3]) -> Vec<u16> {
+    let (red_x, red_y) = match pattern {
+        BayerPattern::Rg => (0, 0),
+        BayerPattern::Gr => (1, 0),
+        BayerPattern::Gb => (0, 1),
+        BayerPattern::Bg => (1, 1),
+    };
+    let mut data = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let channel = match (x % 2 == red_x, y % 2 == red_y) {
+                (true, true) => 0,
+                (false, false) => 2,
+                _ => 1,
+            };
+            data.push(rgb(x, y)[channel]);
+        }
+    }
+    data
+}
+
+fn to_u8(data: &[u16]) -> Vec<u8> {
+    data.iter().map(|&value| value as u8).collect()
+}
+
+#[test]
+fn reference_2x2() {
+    let data: [u8; 4] = [10, 20, 30, 40];
+    for algorithm in ALGORITHMS {
+        let image = debayer(&data, 2, 2, BayerPattern::Rg, algorithm);
+        assert_eq!(image.pixel(0, 0), Some([10, 25, 40]), "{:?}", algorithm);
+    }
+    let image = debayer(&data, 2, 2, BayerPattern::Rg, Algorithm::Bilinear);
+    assert_eq!(image.data, [10, 25, 40, 10, 20, 40, 10, 30, 40, 10, 25, 40]);
+}
+
+#[test]
+fn superpixel_reference() {
+    #[rustfmt::skip]
+    let data: [u8; 16] = [
+        1, 2, 3, 4,
+        5, 6, 7, 8,
+        9, 10, 11, 12,
+        13, 14, 15, 16,
+    ];
+    let image = debayer(&data, 4, 4, BayerPattern::Gb, Algorithm::Superpixel);
+    assert_eq!((image.width, image.height), (2, 2));
+    // Green at (0, 0) and (1, 1), blue at (1, 0), red at (0, 1).
+    assert_eq!(image.data, [5, 4, 2, 7, 6, 4, 13, 12, 10, 15, 14, 12]);
+}
+
+#[test]
+fn uniform_color_is_exact() {
+    for pattern in PATTERNS {
+        for algorithm in ALGORITHMS {
+            let color = [60000, 1000, 65535];
+            let data = mosaic(8, 6, pattern, |_, _| color);
+            let image = debayer(&data, 8, 6, pattern, algorithm);
+            assert!(image.data.chunks_exact(3).all(|pixel| pixel == color), "{:?} {:?}", pattern, algorithm);
+
+            let color = [200, 17, 90];
+            let data = to_u8(&mosaic(8, 6, pattern, |_, _| color.map(u16::from)));
+            let image: RgbImage<u8> = debayer(&data, 8, 6, pattern, algorithm);
+            assert!(image.data.chunks_exact(3).all(|pixel| pixel == color), "{:?} {:?}", pattern, algorithm);
+        }
+    }
+}
+
+#[test]
+fn linear_gradient_is_exact_inside() {
+    let gradient = |x: u32, y: u32| [100 + 40 * x as u16 + 16 * y as u16, 2000 + 12 * x as u16, 9000 - 24 * y as u16 + 8 * x as u16];
+    for pattern in PATTERNS {
+        for algorithm in [Algorithm::Bilinear, Algorithm::MalvarHeCutler] {
+            let data = mosaic(12, 10, pattern, gradient);
+            let image = debayer(&data, 12, 10, pattern, algorithm);
+            for y in 2..8 {
+                for x in 2..10 {
+                    assert_eq!(image.pixel(x, y), Some(gradient(x, y)), "{:?} {:?} at ({}, {})", pattern, algorithm, x, y);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn pattern_shift() {
+    let rg = BayerPattern::Rg;
+    assert_eq!(effective_pattern(rg, 0, 0, 8, 2, FlipStatus::None), BayerPattern::Rg);
+    assert_eq!(effective_pattern(rg, 1, 0, 8, 2, FlipStatus::None), BayerPattern::Gr);
+    assert_eq!(effective_pattern(rg, 0, 3, 8, 2, FlipStatus::None), BayerPattern::Gb);
+    assert_eq!(effective_pattern(rg, 5, 1, 8, 2, FlipStatus::None), BayerPattern::Bg);
+    assert_eq!(effective_pattern(rg, 0, 0, 8, 2, FlipStatus::Horizontal), BayerPattern::Gr);
+    assert_eq!(effective_pattern(rg, 0, 0, 8, 2, FlipStatus::Vertical), BayerPattern::Gb);
+    assert_eq!(effective_pattern(rg, 0, 0, 8, 2, FlipStatus::Both), BayerPattern::Bg);
+    assert_eq!(effective_pattern(BayerPattern::Gb, 1, 0, 8, 2, FlipStatus::Horizontal), BayerPattern::Gb);
+}
+
+#[test]
+fn debayer_frames() {
+    let format = common::format(4, 2, ImgType::Raw16, Some(BayerPattern::Rg), 16);
+    let frame = Frame::new(format);
+    assert!(matches!(frame.debayer(Algorithm::Bilinear), Some(ColorImage::Rgb16(image)) if image.data.len() == 24));
+
+    let frame = Frame::new(FrameFormat { img_type: ImgType::Raw8, ..format });
+    assert!(matches!(frame.debayer(Algorithm::Superpixel), Some(ColorImage::Rgb8(image)) if image.width == 2));
+
+    let frame = Frame::new(FrameFormat { bayer_pattern: None, ..format });
+    assert_eq!(frame.debayer(Algorithm::Bilinear), None);
+}
+
+#[test]
+fn too_small_frames() {
+    for (width, height) in [(1, 4), (4, 1), (1, 1)] {
+        let frame = Frame::new(common::format(width, height, ImgType::Raw8, Some(BayerPattern::Rg), 8));
+        for algorithm in ALGORITHMS {
+            assert_eq!(frame.debayer(algorithm), None, "{}x{}", width, height);
+        }
+    }
+}
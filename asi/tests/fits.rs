@@ -0,0 +1,136 @@
+use asi::fits::{self, Card, FitsWriter, Value};
+use asi::{BayerPattern, Frame, FrameFormat, ImgType};
+
+mod common;
+
+fn frame(img_type: ImgType, bayer_pattern: Option<BayerPattern>) -> Frame {
+    let bit_depth = if img_type == ImgType::Raw16 { 16 } else { 8 };
+    common::frame(FrameFormat { bin: 2, ..common::format(6, 4, img_type, bayer_pattern, bit_depth) }, 0)
+}
+
+fn round_trip(writer: &FitsWriter, frame: &Frame) -> (Vec<u8>, fits::FitsImage) {
+    let mut file = Vec::new();
+    writer.write(frame, &mut file).unwrap();
+    let image = fits::read(file.as_slice()).unwrap();
+    (file, image)
+}
+
+#[test]
+fn raw16_round_trip() {
+    let mut frame = frame(ImgType::Raw16, Some(BayerPattern::Gb));
+    frame.as_u16_mut().unwrap()[..3].copy_from_slice(&[0, 32768, 65535]);
+    let (file, image) = round_trip(&FitsWriter::new(), &frame);
+
+    assert_eq!(file.len() % 2880, 0);
+    assert_eq!(image.frame, frame);
+    assert_eq!(image.value("BITPIX"), Some(&Value::Integer(16)));
+    assert_eq!(image.value("BZERO"), Some(&Value::Integer(32768)));
+    assert_eq!(image.value("BAYERPAT"), Some(&Value::Text("GBRG".into())));
+    assert_eq!(image.value("XBINNING"), Some(&Value::Integer(2)));
+}
+
+#[test]
+fn raw8_round_trip() {
+    let frame = frame(ImgType::Raw8, None);
+    let (_, image) = round_trip(&FitsWriter::new(), &frame);
+    assert_eq!(image.frame, frame);
+    assert_eq!(image.value("BITPIX"), Some(&Value::Integer(8)));
+    assert_eq!(image.value("BAYERPAT"), None);
+}
+
+#[test]
+fn rgb24_is_stored_as_planes() {
+    let frame = frame(ImgType::Rgb24, None);
+    let (file, image) = round_trip(&FitsWriter::new(), &frame);
+    assert_eq!(image.frame, frame);
+    assert_eq!(image.value("NAXIS3"), Some(&Value::Integer(3)));
+
+    // The first plane is red, the last byte of each BGR pixel.
+    let data = &file[2880..];
+    assert_eq!(data[0], frame.as_u8()[2]);
+    assert_eq!(data[24], frame.as_u8()[1]);
+    assert_eq!(data[48], frame.as_u8()[0]);
+}
+
+#[test]
+fn user_cards() {
+    let writer = FitsWriter::new()
+        .card(Card::new("OBJECT", "Barnard's Star").comment("target"))
+        .card(Card::new("FOCALLEN", 1200.0))
+        .card(Card::new("PIERSIDE", false))
+        .card(Card::new("XBINNING", 1));
+    let (_, image) = round_trip(&writer, &frame(ImgType::Raw8, None));
+
+    assert_eq!(image.card("OBJECT"), Some(&Card::new("OBJECT", "Barnard's Star").comment("target")));
+    assert_eq!(image.value("FOCALLEN"), Some(&Value::Real(1200.0)));
+    assert_eq!(image.value("PIERSIDE"), Some(&Value::Logical(false)));
+    assert_eq!(image.cards.iter().filter(|card| card.keyword == "XBINNING").count(), 1);
+    assert_eq!(image.value("XBINNING"), Some(&Value::Integer(1)));
+}
+
+#[test]
+fn rejects_invalid_keyword() {
+    let writer = FitsWriter::new().card(Card::new("object", "M 42"));
+    assert!(writer.write(&frame(ImgType::Raw8, None), Vec::new()).is_err());
+}
+
+#[test]
+fn rejects_structural_keywords() {
+    for keyword in ["SIMPLE", "BITPIX", "NAXIS", "NAXIS1", "NAXIS3", "BZERO", "BSCALE", "END"] {
+        let writer = FitsWriter::new().card(Card::new(keyword, 16));
+        let error = writer.write(&frame(ImgType::Raw16, None), Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "{}", keyword);
+    }
+    // Only NAXIS with a number is structural.
+    let writer = FitsWriter::new().card(Card::new("NAXISX", 1));
+    assert!(writer.write(&frame(ImgType::Raw8, None), Vec::new()).is_ok());
+}
+
+#[test]
+fn keeps_the_bit_depth() {
+    let mut frame = frame(ImgType::Raw16, None);
+    let mut format = *frame.format();
+    format.bit_depth = 12;
+    frame.reformat(format);
+    let (_, image) = round_trip(&FitsWriter::new(), &frame);
+    assert_eq!(image.value("BITDEPTH"), Some(&Value::Integer(12)));
+    assert_eq!(image.frame.bit_depth(), 12);
+    assert_eq!(image.frame, frame);
+
+    // Files of other programs have the bit depth of the data.
+    let writer = FitsWriter::new().card(Card::new("BITDEPTH", 99));
+    let (_, image) = round_trip(&writer, &frame);
+    assert_eq!(image.frame.bit_depth(), 16);
+}
+
+#[test]
+fn rejects_truncated_data() {
+    let mut file = Vec::new();
+    FitsWriter::new().write(&frame(ImgType::Raw16, None), &mut file).unwrap();
+    let error = fits::read(&file[..2880 + 10]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // A huge size in the header doesn't allocate more than the input has.
+    let header = String::from_utf8(file[..2880].to_vec()).unwrap().replace("NAXIS1  =                    6", "NAXIS1  =            400000000");
+    let mut file = header.into_bytes();
+    file.extend([0; 2880]);
+    let error = fits::read(file.as_slice()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn rejects_non_finite_reals() {
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let writer = FitsWriter::new().card(Card::new("FOCALLEN", value));
+        let error = writer.write(&frame(ImgType::Raw8, None), Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "{}", value);
+    }
+
+    // Other programs can't write them either.
+    let mut file = Vec::new();
+    FitsWriter::new().card(Card::new("FOCALLEN", 1200.0)).write(&frame(ImgType::Raw8, None), &mut file).unwrap();
+    let header = String::from_utf8(file[..2880].to_vec()).unwrap().replace("              1200.0", "                 NaN");
+    let mut file = header.into_bytes();
+    file.extend([0; 2880]);
+    assert_eq!(fits::read(file.as_slice()).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,64 @@
+use asi::{Frame, FrameFormat, ImgType, Pixel};
+
+mod common;
+
+fn format(width: u32, height: u32, img_type: ImgType) -> FrameFormat {
+    common::format(width, height, img_type, None, 12)
+}
+
+#[test]
+fn rgb24_pixels_are_in_rgb_order() {
+    // The SDK delivers blue, green, red.
+    let frame = Frame::from_bytes(format(2, 1, ImgType::Rgb24), &[1, 2, 3, 40, 50, 60]).unwrap();
+    assert_eq!(frame.pixel(0, 0), Some(Pixel::Rgb([3, 2, 1])));
+    assert_eq!(frame.pixel(1, 0), Some(Pixel::Rgb([60, 50, 40])));
+    assert_eq!(frame.pixel(2, 0), None);
+    assert_eq!(frame.pixel(0, 1), None);
+    assert_eq!(frame.as_u8(), [1, 2, 3, 40, 50, 60]);
+    assert!(frame.as_u16().is_none());
+}
+
+#[test]
+fn mono8_pixels() {
+    let frame = Frame::from_bytes(format(3, 2, ImgType::Raw8), &[1, 2, 3, 4, 5, 6]).unwrap();
+    assert_eq!(frame.pixel(2, 1), Some(Pixel::Mono8(6)));
+    assert_eq!(frame.as_u8().len(), 6);
+    assert!(frame.as_u16().is_none());
+    let frame = Frame::from_bytes(format(1, 1, ImgType::Y8), &[9]).unwrap();
+    assert_eq!(frame.pixel(0, 0), Some(Pixel::Mono8(9)));
+}
+
+#[test]
+fn raw16_u16_view() {
+    let values = [0x0010u16, 0xfff0, 0x1230, 0x8000];
+    let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_ne_bytes()).collect();
+    let mut frame = Frame::from_bytes(format(2, 2, ImgType::Raw16), &bytes).unwrap();
+    assert_eq!(frame.as_u16().unwrap(), values);
+    assert_eq!(frame.as_u8(), bytes);
+    assert_eq!(frame.pixel(1, 1), Some(Pixel::Mono16(0x8000)));
+
+    frame.as_u16_mut().unwrap()[0] = 0xabc0;
+    assert_eq!(frame.pixel(0, 0), Some(Pixel::Mono16(0xabc0)));
+    assert_eq!(frame.as_u8()[..2], 0xabc0u16.to_ne_bytes());
+}
+
+#[test]
+fn from_bytes_checks_the_length() {
+    assert!(Frame::from_bytes(format(2, 2, ImgType::Raw16), &[0; 7]).is_none());
+    assert!(Frame::from_bytes(format(2, 2, ImgType::Rgb24), &[0; 12]).is_some());
+}
+
+#[test]
+fn reformat() {
+    let mut frame = Frame::from_bytes(format(3, 1, ImgType::Raw8), &[1, 2, 3]).unwrap();
+    frame.reformat(format(4, 2, ImgType::Raw16));
+    assert_eq!(frame.format(), &format(4, 2, ImgType::Raw16));
+    assert_eq!(frame.as_u8().len(), 16);
+    assert_eq!(frame.as_u16().unwrap().len(), 8);
+
+    frame.reformat(format(2, 1, ImgType::Rgb24));
+    assert_eq!(frame.as_u8().len(), 6);
+    assert!(frame.as_u16().is_none());
+    assert!(frame.pixel(1, 0).is_some());
+    assert!(frame.pixel(2, 0).is_none());
+}
@@ -0,0 +1,52 @@
+use asi::{GpsData, GpsDateTime};
+use asi_sys::*;
+
+fn sdk_gps_data() -> ASI_GPS_DATA {
+    let mut data = ASI_GPS_DATA::default();
+    data.Datetime.Year = 2024;
+    data.Datetime.Month = 3;
+    data.Datetime.Day = 1;
+    data.Datetime.Hour = 20;
+    data.Datetime.Minute = 44;
+    data.Datetime.Second = 13;
+    data.Datetime.Msecond = 250;
+    data.Datetime.Usecond = 1234;
+    data.Latitude = 48.1486;
+    data.Longitude = -17.1077;
+    data.Altitude = 1405;
+    data.SatelliteNum = 9;
+    data
+}
+
+#[test]
+fn converts_sdk_gps_data() {
+    let data = GpsData::from(sdk_gps_data());
+    assert_eq!(data.datetime, GpsDateTime { year: 2024, month: 3, day: 1, hour: 20, minute: 44, second: 13, nanosecond: 250_123_400 });
+    assert_eq!(data.datetime.microsecond(), 250_123);
+    assert_eq!(data.latitude, 48.1486);
+    assert_eq!(data.longitude, -17.1077);
+    assert_eq!(data.altitude, 140.5);
+    assert_eq!(data.satellites, 9);
+}
+
+#[test]
+fn formats_datetime_with_tenth_of_microseconds() {
+    let data = GpsData::from(sdk_gps_data());
+    assert_eq!(data.datetime.to_string(), "2024-03-01T20:44:13.2501234");
+    assert_eq!(GpsDateTime::default().to_string(), "0000-00-00T00:00:00.0000000");
+}
+
+#[test]
+fn clamps_invalid_fractions() {
+    let mut data = sdk_gps_data();
+    data.Datetime.Msecond = 5000;
+    data.Datetime.Usecond = 20000;
+    assert_eq!(GpsDateTime::from(data.Datetime).nanosecond, 999_999_900);
+
+    data.Datetime.Msecond = i32::MAX;
+    data.Datetime.Usecond = -1;
+    assert_eq!(GpsDateTime::from(data.Datetime).nanosecond, 999_000_000);
+
+    data.Datetime.Msecond = i32::MIN;
+    assert_eq!(GpsDateTime::from(data.Datetime).nanosecond, 0);
+}
@@ -0,0 +1,91 @@
+use asi::{CameraInfo, Error, ImgType, Roi};
+use asi_sys::*;
+
+mod common;
+
+fn camera_info(name: &str, max_width: i64, max_height: i64, usb3: bool) -> CameraInfo {
+    let mut raw = ASI_CAMERA_INFO { MaxWidth: max_width as _, MaxHeight: max_height as _, IsUSB3Camera: usb3.into(), ..Default::default() };
+    common::fill(&mut raw.Name, name.as_bytes());
+    raw.SupportedBins[..4].copy_from_slice(&[1, 2, 3, 4]);
+    raw.SupportedVideoFormat[..5].copy_from_slice(&[0, 1, 2, 3, -1]);
+    CameraInfo::from(raw)
+}
+
+fn asi294() -> CameraInfo {
+    camera_info("ZWO ASI294MC Pro", 4144, 2822, true)
+}
+
+fn asi120() -> CameraInfo {
+    camera_info("ZWO ASI120MM", 1280, 960, false)
+}
+
+fn roi(width: u32, height: u32, start_x: u32, start_y: u32) -> Roi {
+    Roi { width, height, bin: 1, img_type: ImgType::Raw8, start_x, start_y }
+}
+
+fn reason(result: asi::Result<Roi>) -> String {
+    match result {
+        Err(Error::InvalidRoi { reason, .. }) => reason,
+        other => panic!("expected an invalid ROI, got {:?}", other),
+    }
+}
+
+#[test]
+fn full_sensor() {
+    let info = asi294();
+    assert_eq!(Roi::full().build(&info).unwrap(), roi(4144, 2822, 0, 0));
+    let binned = Roi::full().bin(3).format(ImgType::Raw16).build(&info).unwrap();
+    assert_eq!(binned, Roi { width: 1376, height: 940, bin: 3, img_type: ImgType::Raw16, start_x: 2, start_y: 0 });
+}
+
+#[test]
+fn centered() {
+    assert_eq!(Roi::centered(640, 480).build(&asi294()).unwrap(), roi(640, 480, 1752, 1171));
+    assert_eq!(Roi::centered(640, 480).at(8, 4).build(&asi294()).unwrap(), roi(640, 480, 8, 4));
+}
+
+#[test]
+fn width_must_be_multiple_of_8() {
+    assert!(reason(Roi::centered(100, 100).build(&asi294())).contains("multiple of 8"));
+    assert!(reason(Roi::centered(96, 99).build(&asi294())).contains("multiple of 2"));
+    assert_eq!(Roi::centered(100, 99).snap(&asi294()).unwrap(), roi(104, 100, 2020, 1361));
+}
+
+#[test]
+fn unsupported_bin_and_format() {
+    assert!(reason(Roi::full().bin(5).snap(&asi294())).contains("bin 5"));
+    let mut info = asi294();
+    info.supported_video_formats = vec![ImgType::Raw8];
+    assert!(reason(Roi::full().format(ImgType::Raw16).build(&info)).contains("Raw16"));
+}
+
+#[test]
+fn asi120_needs_multiple_of_1024_pixels() {
+    assert_eq!(Roi::full().build(&asi120()).unwrap(), roi(1280, 960, 0, 0));
+    assert!(reason(Roi::centered(648, 482).build(&asi120())).contains("1024"));
+    assert_eq!(Roi::centered(648, 482).snap(&asi120()).unwrap(), roi(648, 384, 316, 288));
+    // The USB3 version doesn't have the limitation.
+    assert!(Roi::centered(648, 482).build(&camera_info("ZWO ASI120MM-S", 1280, 960, true)).is_ok());
+}
+
+#[test]
+fn outside_of_the_sensor() {
+    let info = asi294();
+    assert!(reason(Roi::centered(640, 480).at(3600, 0).build(&info)).contains("outside"));
+    assert!(reason(Roi::centered(640, 480).bin(2).at(1500, 0).build(&info)).contains("2072x1411"));
+    assert_eq!(Roi::centered(640, 480).at(3600, 2700).snap(&info).unwrap(), roi(640, 480, 3504, 2342));
+    assert_eq!(Roi::centered(8000, 8000).snap(&info).unwrap(), roi(4144, 2822, 0, 0));
+}
+
+#[test]
+fn overflowing_input() {
+    let info = asi294();
+    assert!(reason(Roi::centered(64, 64).at(u32::MAX, 0).build(&info)).contains("outside"));
+    assert!(reason(Roi::centered(64, 64).at(0, u32::MAX - 10).build(&info)).contains("outside"));
+    assert_eq!(Roi::centered(u32::MAX, 64).snap(&info).unwrap(), roi(4144, 64, 0, 1379));
+    assert_eq!(Roi::centered(64, u32::MAX).at(u32::MAX, u32::MAX).snap(&info).unwrap(), roi(64, 2822, 4080, 0));
+
+    let huge = roi(1 << 16, 1 << 16, 0, 0);
+    assert!(matches!(huge.validate(&asi120()), Err(Error::InvalidRoi { .. })));
+    assert!(matches!(huge.validate(&info), Err(Error::InvalidRoi { .. })));
+}
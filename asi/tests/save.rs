@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use asi::{Celsius, Frame, FrameMetadata, ImgType, Roi, Timestamp};
+
+mod common;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("asi-save-{}-{}", std::process::id(), name))
+}
+
+fn frame(img_type: ImgType) -> Frame {
+    let mut frame = common::frame(common::format(4, 2, img_type, None, 12), 0);
+    frame.set_metadata(Some(FrameMetadata {
+        sequence: 7,
+        start: Timestamp::now(),
+        end: Timestamp::now(),
+        exposure: Some(Duration::from_millis(250)),
+        gain: Some(120),
+        offset: Some(10),
+        temperature: Some(Celsius(-9.5)),
+        target_temperature: None,
+        roi: Roi { width: 4, height: 2, bin: 1, img_type, start_x: 0, start_y: 0 },
+        serial_number: None,
+        camera_name: "ZWO ASI533MC Pro\u{FFFD}".into(),
+        gps: None,
+    }));
+    frame
+}
+
+#[test]
+fn png_keeps_16_bits_and_metadata() {
+    let frame = frame(ImgType::Raw16);
+    let path = temp_path("raw16.png");
+    frame.save(&path).unwrap();
+
+    let mut reader = png::Decoder::new(BufReader::new(File::open(&path).unwrap())).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut data).unwrap();
+    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+    let pixels: Vec<u16> = data.chunks_exact(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]])).collect();
+    assert_eq!(pixels, frame.as_u16().unwrap());
+
+    let text: Vec<(String, String)> = reader.info().uncompressed_latin1_text.iter().map(|chunk| (chunk.keyword.clone(), chunk.text.clone())).collect();
+    assert!(text.contains(&("Source".into(), "ZWO ASI533MC Pro?".into())));
+    assert!(!text.iter().any(|(keyword, _)| keyword == "Camera" || keyword == "Date"));
+    assert!(text.contains(&("Exposure".into(), "0.25 s".into())));
+    assert!(text.contains(&("Gain".into(), "120".into())));
+    assert!(text.contains(&("Temperature".into(), "-9.5 C".into())));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn tiff_converts_bgr_to_rgb() {
+    let frame = frame(ImgType::Rgb24);
+    let path = temp_path("rgb24.tiff");
+    frame.save(&path).unwrap();
+
+    let mut decoder = tiff::decoder::Decoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+    let tiff::decoder::DecodingResult::U8(data) = decoder.read_image().unwrap() else {
+        panic!("expected 8 bit data");
+    };
+    assert_eq!(&data[..3], &[frame.as_u8()[2], frame.as_u8()[1], frame.as_u8()[0]]);
+    assert_eq!(decoder.get_tag_ascii_string(tiff::tags::Tag::Model).unwrap(), "ZWO ASI533MC Pro?");
+    let description = decoder.get_tag_ascii_string(tiff::tags::Tag::ImageDescription).unwrap();
+    assert!(description.contains("Gain: 120"));
+    assert!(description.contains("Exposure: 0.25 s"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn fits_by_extension() {
+    let frame = frame(ImgType::Raw8);
+    let path = temp_path("raw8.fits");
+    frame.save(&path).unwrap();
+    let image = asi::fits::open(&path).unwrap();
+    assert_eq!(image.frame.as_u8(), frame.as_u8());
+    assert_eq!(image.value("GAIN"), Some(&asi::fits::Value::Integer(120)));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unknown_extension() {
+    assert!(frame(ImgType::Raw8).save(temp_path("raw8.jpg")).is_err());
+}
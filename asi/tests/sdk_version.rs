@@ -0,0 +1,26 @@
+use asi::{Error, SdkVersion};
+
+#[test]
+fn parses_sdk_format() {
+    assert_eq!("1, 13, 0930".parse::<SdkVersion>().unwrap(), SdkVersion::new(1, 13, 930));
+    assert_eq!("1,13,0930".parse::<SdkVersion>().unwrap(), SdkVersion::new(1, 13, 930));
+    assert_eq!("  1 ,\t13 , 0930\n".parse::<SdkVersion>().unwrap(), SdkVersion::new(1, 13, 930));
+    assert_eq!(SdkVersion::new(1, 13, 930).to_string(), "1.13.0930");
+}
+
+#[test]
+fn rejects_malformed_versions() {
+    for version in ["", "1, 13", "1, 13, 0930, 2", "1, 13, x", "1.13.0930", "1, -13, 0930", "1, , 0930"] {
+        match version.parse::<SdkVersion>() {
+            Err(Error::InvalidSdkVersion(invalid)) => assert_eq!(invalid, version),
+            other => panic!("{:?} parsed as {:?}", version, other),
+        }
+    }
+}
+
+#[test]
+fn orders_by_major_minor_build() {
+    let versions = ["1, 9, 1200", "1, 13, 0503", "1, 13, 0930", "2, 0, 0001"].map(|version| version.parse::<SdkVersion>().unwrap());
+    assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(SdkVersion::new(1, 13, 930) >= SdkVersion::new(1, 13, 930));
+}
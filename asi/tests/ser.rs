@@ -0,0 +1,105 @@
+use std::io::Cursor;
+use std::time::{Duration, UNIX_EPOCH};
+
+use asi::ser::{ColorId, SerReader, SerWriter};
+use asi::{BayerPattern, Frame, ImgType};
+
+mod common;
+
+fn frames(img_type: ImgType, bayer_pattern: Option<BayerPattern>) -> Vec<Frame> {
+    let bit_depth = if img_type == ImgType::Raw16 { 16 } else { 8 };
+    let format = common::format(8, 4, img_type, bayer_pattern, bit_depth);
+    (0..3).map(|index| common::frame(format, index)).collect()
+}
+
+fn record(frames: &[Frame]) -> Vec<u8> {
+    let mut writer = SerWriter::new(Cursor::new(Vec::new())).observer("Tester").telescope("C11");
+    for frame in frames {
+        writer.write_frame(frame).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn raw16_bayer_round_trip() {
+    let frames = frames(ImgType::Raw16, Some(BayerPattern::Bg));
+    let file = record(&frames);
+    assert_eq!(file.len(), 178 + 3 * 64 + 3 * 8);
+    // 16 bit data is little endian.
+    let first = frames[0].as_u16().unwrap()[0];
+    assert_eq!(&file[178..180], &first.to_le_bytes());
+
+    let mut reader = SerReader::new(Cursor::new(file)).unwrap();
+    let header = reader.header().clone();
+    assert_eq!(header.color_id, ColorId::Bayer(BayerPattern::Bg));
+    assert_eq!((header.width, header.height, header.pixel_depth, header.frame_count), (8, 4, 16, 3));
+    assert_eq!((header.observer.as_str(), header.telescope.as_str()), ("Tester", "C11"));
+    assert!(header.little_endian);
+
+    for (index, frame) in frames.iter().enumerate() {
+        assert_eq!(reader.read_frame(index).unwrap().as_ref(), Some(frame));
+        assert!(reader.timestamp(index).unwrap() > UNIX_EPOCH + Duration::from_secs(1_600_000_000));
+    }
+    assert_eq!(reader.read_frame(3).unwrap(), None);
+    assert_eq!(header.date_time_utc, reader.timestamp(0));
+}
+
+#[test]
+fn mono_and_color_round_trip() {
+    for (img_type, color_id) in [(ImgType::Raw8, ColorId::Mono), (ImgType::Rgb24, ColorId::Bgr)] {
+        let frames = frames(img_type, None);
+        let mut reader = SerReader::new(Cursor::new(record(&frames))).unwrap();
+        assert_eq!(reader.header().color_id, color_id);
+        assert_eq!(reader.header().pixel_depth, 8);
+        for (index, frame) in frames.iter().enumerate() {
+            assert_eq!(reader.read_frame(index).unwrap().as_ref(), Some(frame));
+        }
+    }
+}
+
+#[test]
+fn dropped_writer_is_finished() {
+    let frames = frames(ImgType::Raw8, None);
+    let mut file = Cursor::new(Vec::new());
+    {
+        let mut writer = SerWriter::new(&mut file);
+        writer.write_frame(&frames[0]).unwrap();
+    }
+    let reader = SerReader::new(Cursor::new(file.into_inner())).unwrap();
+    assert_eq!(reader.frame_count(), 1);
+    assert!(reader.timestamp(0).is_some());
+}
+
+#[test]
+fn rejects_mismatching_frame() {
+    let mut writer = SerWriter::new(Cursor::new(Vec::new()));
+    writer.write_frame(&frames(ImgType::Raw8, None)[0]).unwrap();
+    assert!(writer.write_frame(&frames(ImgType::Raw16, None)[0]).is_err());
+}
+
+#[test]
+fn rejects_corrupt_timestamps() {
+    let mut file = record(&frames(ImgType::Raw8, None));
+    let trailer = file.len() - 3 * 8;
+    file[trailer..trailer + 8].copy_from_slice(&i64::MAX.to_le_bytes());
+    let error = SerReader::new(Cursor::new(file)).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn frames_are_bounded_by_the_file() {
+    // A truncated file has no trailer and its last frame can't be read.
+    let file = record(&frames(ImgType::Raw8, None));
+    let mut reader = SerReader::new(Cursor::new(file[..178 + 2 * 32 + 10].to_vec())).unwrap();
+    assert!(reader.timestamp(0).is_none());
+    assert!(reader.read_frame(1).unwrap().is_some());
+    assert_eq!(reader.read_frame(2).unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // A huge size in the header doesn't allocate the frame.
+    let mut file = file;
+    file[26..30].copy_from_slice(&i32::MAX.to_le_bytes());
+    file[30..34].copy_from_slice(&i32::MAX.to_le_bytes());
+    let mut reader = SerReader::new(Cursor::new(file)).unwrap();
+    assert!(reader.timestamp(0).is_none());
+    assert_eq!(reader.read_frame(0).unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+}
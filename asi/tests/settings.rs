@@ -0,0 +1,93 @@
+use asi::{is_setting, CameraMode, CameraSettings, ControlSetting, ControlType, ImgType, Roi, SettingChange, TrigOutput, TriggerOutputConfig};
+
+fn settings() -> CameraSettings {
+    CameraSettings {
+        camera_mode: Some(CameraMode::Normal),
+        roi: Roi { width: 1920, height: 1080, bin: 1, img_type: ImgType::Raw16, start_x: 64, start_y: 32 },
+        trigger_outputs: vec![
+            TriggerOutputConfig { pin: TrigOutput::PinA, pin_high: true, delay: 0, duration: 1000 },
+            TriggerOutputConfig { pin: TrigOutput::PinB, pin_high: false, delay: 10, duration: 0 },
+        ],
+        controls: vec![
+            ControlSetting { control_type: ControlType::Gain, value: 120, auto: false },
+            ControlSetting { control_type: ControlType::Exposure, value: 30_000, auto: true },
+            ControlSetting { control_type: ControlType::Offset, value: 8, auto: false },
+        ],
+    }
+}
+
+fn change(setting: &str, from: &str, to: &str) -> SettingChange {
+    SettingChange { setting: setting.to_string(), from: from.to_string(), to: to.to_string() }
+}
+
+#[test]
+fn json_round_trip() {
+    let settings = settings();
+    let json = serde_json::to_string_pretty(&settings).unwrap();
+    assert_eq!(serde_json::from_str::<CameraSettings>(&json).unwrap(), settings);
+}
+
+#[test]
+fn values_outside_of_i32_are_kept() {
+    let mut settings = settings();
+    settings.controls[1].value = 3_000_000_000;
+    let json = serde_json::to_string(&settings).unwrap();
+    assert_eq!(serde_json::from_str::<CameraSettings>(&json).unwrap().controls[1].value, 3_000_000_000);
+}
+
+#[test]
+fn toml_round_trip() {
+    let mut settings = settings();
+    let toml = toml::to_string(&settings).unwrap();
+    assert_eq!(toml::from_str::<CameraSettings>(&toml).unwrap(), settings);
+
+    settings.camera_mode = None;
+    settings.trigger_outputs.clear();
+    let toml = toml::to_string(&settings).unwrap();
+    assert_eq!(toml::from_str::<CameraSettings>(&toml).unwrap(), settings);
+}
+
+#[test]
+fn equal_snapshots_have_no_diff() {
+    assert!(settings().diff(&settings()).is_empty());
+}
+
+#[test]
+fn diff_lists_changed_settings() {
+    let mut other = settings();
+    other.camera_mode = None;
+    other.roi.bin = 2;
+    other.roi.img_type = ImgType::Raw8;
+    other.trigger_outputs.remove(1);
+    other.controls[0].value = 200;
+    other.controls[1].auto = false;
+    other.controls.remove(2);
+    other.controls.push(ControlSetting { control_type: ControlType::TargetTemp, value: -10, auto: false });
+
+    let changes = settings().diff(&other);
+    assert_eq!(
+        changes,
+        [
+            change("camera_mode", "Normal", "-"),
+            change("bin", "1", "2"),
+            change("img_type", "Raw16", "Raw8"),
+            change("trigger_output PinB", "pin_high: false, delay: 10, duration: 0", "-"),
+            change("Gain", "120", "200"),
+            change("Exposure", "30000 (auto)", "30000"),
+            change("Offset", "8", "-"),
+            change("TargetTemp", "-", "-10"),
+        ]
+    );
+    assert_eq!(changes[1].to_string(), "bin: 1 -> 2");
+}
+
+#[test]
+fn action_controls_are_not_settings() {
+    assert!(is_setting(ControlType::Gain));
+    assert!(is_setting(ControlType::TargetTemp));
+    assert!(!is_setting(ControlType::UsbHubReset));
+    assert!(!is_setting(ControlType::GpsStartLine));
+    assert!(!is_setting(ControlType::GpsEndLine));
+    assert!(!is_setting(ControlType::Temperature));
+    assert!(!is_setting(ControlType::Unknown(99)));
+}
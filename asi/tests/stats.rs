@@ -0,0 +1,93 @@
+use asi::{BayerPattern, Channel, Frame, ImgType, StatsOptions};
+
+mod common;
+
+fn frame(img_type: ImgType, bayer_pattern: Option<BayerPattern>, width: u32, height: u32, bit_depth: u32) -> Frame {
+    Frame::new(common::format(width, height, img_type, bayer_pattern, bit_depth))
+}
+
+#[test]
+fn mono_stats() {
+    let mut frame = frame(ImgType::Raw8, None, 4, 2, 8);
+    frame.as_u8_mut().copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 255]);
+    let stats = frame.stats();
+    assert_eq!(stats.channels.len(), 1);
+    let mono = stats.channel(Channel::Mono).unwrap();
+    assert_eq!((mono.count, mono.min, mono.max, mono.saturated), (8, 1, 255, 1));
+    assert_eq!(mono.mean, 283.0 / 8.0);
+    assert_eq!(mono.median, 4.5);
+    // Deviations from 4.5 are 3.5, 2.5, 1.5, 0.5, 0.5, 1.5, 2.5 and 250.5.
+    assert_eq!(mono.mad, 2.0);
+    let expected = (([1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 255.0].iter().map(|v| v * v).sum::<f64>() / 8.0) - mono.mean * mono.mean).sqrt();
+    assert!((mono.stddev - expected).abs() < 1e-9);
+    assert_eq!(mono.histogram.counts.len(), 256);
+    assert_eq!(mono.histogram.counts[255], 1);
+}
+
+#[test]
+fn raw16_uses_native_adu() {
+    let mut frame = frame(ImgType::Raw16, None, 4, 1, 12);
+    frame.as_u16_mut().unwrap().copy_from_slice(&[0x0010, 0x0020, 0x8000, 0xfff0]);
+    let stats = frame.stats_with(&StatsOptions { bins: 16, subsample: 1 });
+    let mono = stats.channel(Channel::Mono).unwrap();
+    assert_eq!((mono.min, mono.max, mono.saturated), (1, 4095, 1));
+    assert_eq!(mono.histogram.max_value, 4095);
+    assert_eq!(mono.histogram.counts[0], 2);
+    assert_eq!(mono.histogram.counts[8], 1);
+    assert_eq!(mono.histogram.counts[15], 1);
+    assert_eq!(mono.histogram.bin_range(0), (0, 255));
+    assert_eq!(mono.histogram.bin_range(15), (3840, 4095));
+}
+
+#[test]
+fn bayer_channels() {
+    let mut frame = frame(ImgType::Raw8, Some(BayerPattern::Gr), 4, 2, 8);
+    // G R G R / B G B G
+    frame.as_u8_mut().copy_from_slice(&[50, 10, 50, 12, 100, 52, 102, 52]);
+    let stats = frame.stats();
+    assert_eq!(stats.channel(Channel::Red).unwrap().mean, 11.0);
+    assert_eq!(stats.channel(Channel::Green).unwrap().median, 51.0);
+    assert_eq!(stats.channel(Channel::Blue).unwrap().max, 102);
+    assert_eq!(stats.channel(Channel::Mono), None);
+}
+
+#[test]
+fn rgb_channels() {
+    let mut frame = frame(ImgType::Rgb24, None, 2, 1, 8);
+    // Stored as blue, green, red.
+    frame.as_u8_mut().copy_from_slice(&[1, 2, 3, 5, 6, 7]);
+    let stats = frame.stats();
+    assert_eq!(stats.channel(Channel::Red).unwrap().mean, 5.0);
+    assert_eq!(stats.channel(Channel::Green).unwrap().mean, 4.0);
+    assert_eq!(stats.channel(Channel::Blue).unwrap().mean, 3.0);
+}
+
+#[test]
+fn subsampling_keeps_bayer_blocks() {
+    let mut frame = frame(ImgType::Raw8, Some(BayerPattern::Rg), 8, 8, 8);
+    frame.as_u8_mut().iter_mut().enumerate().for_each(|(i, value)| *value = i as u8);
+    let stats = frame.stats_with(&StatsOptions { subsample: 2, ..StatsOptions::default() });
+    // Blocks at (0, 0), (4, 0), (0, 4) and (4, 4).
+    assert_eq!(stats.channel(Channel::Red).unwrap().count, 4);
+    assert_eq!(stats.channel(Channel::Green).unwrap().count, 8);
+    assert_eq!(stats.channel(Channel::Blue).unwrap().count, 4);
+    assert_eq!(stats.channel(Channel::Red).unwrap().max, 36);
+}
+
+#[test]
+fn subsampling_odd_sizes() {
+    let mut mono = frame(ImgType::Raw8, None, 7, 5, 8);
+    mono.as_u8_mut().iter_mut().enumerate().for_each(|(i, value)| *value = i as u8);
+    let stats = mono.stats_with(&StatsOptions { subsample: 3, ..StatsOptions::default() });
+    // Columns 0, 3 and 6 of rows 0 and 3.
+    let mono = stats.channel(Channel::Mono).unwrap();
+    assert_eq!(mono.count, 6);
+    assert_eq!(mono.max, 27);
+
+    // The incomplete block at the edge is used as far as it goes.
+    let bayer = frame(ImgType::Raw8, Some(BayerPattern::Rg), 5, 5, 8);
+    let stats = bayer.stats_with(&StatsOptions { subsample: 2, ..StatsOptions::default() });
+    assert_eq!(stats.channel(Channel::Red).unwrap().count, 4);
+    assert_eq!(stats.channel(Channel::Green).unwrap().count, 4);
+    assert_eq!(stats.channel(Channel::Blue).unwrap().count, 1);
+}
@@ -0,0 +1,28 @@
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use asi::Timestamp;
+
+fn at(seconds: u64, micros: u32) -> Timestamp {
+    Timestamp {
+        monotonic: Instant::now(),
+        utc: UNIX_EPOCH + Duration::from_secs(seconds) + Duration::from_micros(micros.into()),
+    }
+}
+
+#[test]
+fn utc_string_of_epoch() {
+    assert_eq!(at(0, 0).utc_string(), "1970-01-01T00:00:00.000000");
+}
+
+#[test]
+fn utc_string_handles_leap_days() {
+    assert_eq!(at(951_782_400, 0).utc_string(), "2000-02-29T00:00:00.000000");
+    assert_eq!(at(1_709_251_199, 999_999).utc_string(), "2024-02-29T23:59:59.999999");
+    assert_eq!(at(1_709_325_853, 250_000).utc_string(), "2024-03-01T20:44:13.250000");
+}
+
+#[test]
+fn utc_string_at_end_of_year() {
+    assert_eq!(at(1_735_689_599, 0).utc_string(), "2024-12-31T23:59:59.000000");
+    assert_eq!(at(1_735_689_600, 0).utc_string(), "2025-01-01T00:00:00.000000");
+}